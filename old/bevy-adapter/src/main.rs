@@ -1,19 +1,69 @@
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, EguiPlugin, EguiPrimaryContextPass, egui};
+use outpost_3_core::persistence::{FileEventStore, SnapshotStore, SnapshotStoreError};
 use outpost_3_core::{Command, *};
+use std::path::Path;
+
+const SAVE_DIR: &str = "saves/session";
 
 #[derive(Resource)]
 struct StateStore {
     state: GameState,
+    next_offset: u64,
+    last_rejection: Option<CommandRejected>,
+    persistence: Option<Persistence>,
+    /// Why the session is not being saved, shown to the player
+    persistence_error: Option<String>,
+    /// Class picked for the next launch
+    probe_class: ProbeClass,
+}
+
+struct Persistence {
+    events: FileEventStore,
+    snapshots: SnapshotStore,
+}
+
+impl StateStore {
+    /// Resume the session saved in `SAVE_DIR`, or start fresh if it cannot be opened.
+    fn open() -> Self {
+        let mut store = Self {
+            state: GameState::new(),
+            next_offset: 0,
+            last_rejection: None,
+            persistence: None,
+            persistence_error: None,
+            probe_class: ProbeClass::default(),
+        };
+
+        match open_persistence() {
+            Ok((persistence, state)) => {
+                store.next_offset = persistence.events.next_offset();
+                store.state = state;
+                store.persistence = Some(persistence);
+            }
+            Err(err) => {
+                warn!("Running without persistence: {err}");
+                store.persistence_error = Some(format!("Not saving this session: {err}"));
+            }
+        }
+
+        store
+    }
+}
+
+fn open_persistence() -> std::result::Result<(Persistence, GameState), SnapshotStoreError> {
+    let dir = Path::new(SAVE_DIR);
+    let events = FileEventStore::open(dir.join("events"))?;
+    let snapshots = SnapshotStore::open(dir.join("snapshots"))?;
+    let state = snapshots.restore(&events, GameState::new())?;
+    Ok((Persistence { events, snapshots }, state))
 }
 
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_plugins(EguiPlugin::default())
-        .insert_resource(StateStore {
-            state: GameState::new(),
-        })
+        .insert_resource(StateStore::open())
         .add_systems(Startup, setup_camera_system)
         .add_systems(EguiPrimaryContextPass, ui_system)
         .run();
@@ -28,16 +78,110 @@ fn ui_system(mut contexts: EguiContexts, mut store: ResMut<StateStore>) -> Resul
         ui.heading("Colony Simulation");
         ui.label(format!("Game Time: {:.1}h", store.state.game_time));
 
+        ui.label(format!("Known systems: {}", store.state.systems.len()));
+
+        if store.state.systems.is_empty() && ui.button("Initialize galaxy").clicked() {
+            dispatch(
+                &mut store,
+                Command::InitializeGalaxy {
+                    seed: 42,
+                    star_count: 100,
+                },
+            );
+        }
+
         if ui.button("Advance 10h").clicked() {
-            let ctx = systems::ReducerContext {
-                current_offset: 0,
-                game_time: store.state.game_time,
-            };
-            let (new_state, _events) =
-                systems::reduce(store.state.clone(), Command::AdvanceTime { dt: 10.0 }, ctx);
-            store.state = new_state;
+            dispatch(&mut store, Command::AdvanceTime { dt: 10.0 });
+        }
+
+        if !store.state.systems.is_empty() {
+            ui.separator();
+            ui.label("Probe class");
+            for class in [
+                ProbeClass::Scout,
+                ProbeClass::Survey,
+                ProbeClass::DeepSurvey,
+            ] {
+                let spec = class.spec();
+                let label = format!(
+                    "{class}: {} ly range, {} credits",
+                    spec.max_range_ly, spec.cost
+                );
+                ui.radio_value(&mut store.probe_class, class, label);
+            }
+
+            let target = next_target(&store.state).map(|system| (system.id, system.name.clone()));
+            if let Some((target_system_id, name)) = target
+                && ui.button(format!("Launch probe to {name}")).clicked()
+            {
+                let probe_class = store.probe_class;
+                dispatch(
+                    &mut store,
+                    Command::LaunchProbe {
+                        target_system_id,
+                        origin_system_id: None,
+                        probe_class,
+                        waypoints: Vec::new(),
+                    },
+                );
+            }
+        }
+
+        if let Some(rejected) = &store.last_rejection {
+            ui.colored_label(egui::Color32::RED, &rejected.message);
+        }
+        if let Some(error) = &store.persistence_error {
+            ui.colored_label(egui::Color32::YELLOW, error);
         }
     });
 
     Ok(())
 }
+
+/// The nearest star not yet scanned that no probe is heading to
+fn next_target(state: &GameState) -> Option<&StarSystem> {
+    state
+        .systems
+        .values()
+        .filter(|system| !system.is_discovered())
+        .filter(|system| {
+            let targeted = |probe: &ProbeInFlight| probe.target_system_id == system.id;
+            !state.probes_in_flight.values().any(targeted)
+        })
+        .min_by(|a, b| a.distance_from_sol.total_cmp(&b.distance_from_sol))
+}
+
+fn dispatch(store: &mut StateStore, command: Command) {
+    let ctx = systems::ReducerContext::new(&store.state, store.next_offset);
+    let (new_state, events) = match systems::reduce(store.state.clone(), command.clone(), ctx) {
+        Ok(result) => result,
+        Err(error) => {
+            store.last_rejection = Some(CommandRejected::new(command, error));
+            return;
+        }
+    };
+    store.last_rejection = None;
+
+    // The events must be on disk before the state moves on; otherwise every
+    // later append is refused for continuing from the wrong offset. Should
+    // saving fail, the command is dropped and the session stops saving.
+    if let Some(persistence) = &mut store.persistence
+        && let Err(err) = persistence.events.append(&events)
+    {
+        error!("Failed to save events, no longer saving: {err}");
+        store.persistence = None;
+        store.persistence_error = Some(format!("Saving stopped: {err}"));
+        return;
+    }
+    store.state = new_state;
+    store.next_offset += events.len() as u64;
+
+    // A missed snapshot only makes the next restore replay more of the log
+    if let Some(persistence) = &mut store.persistence
+        && let Err(err) = persistence
+            .snapshots
+            .maybe_save(store.next_offset, &store.state)
+    {
+        warn!("Failed to save snapshot: {err}");
+    }
+}
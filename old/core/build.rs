@@ -1,12 +1,17 @@
 use std::env;
+use std::path::Path;
 
 fn main() {
     let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
-    
+    // Only the FFI module is the C API; parsing the whole crate would also
+    // export its public consts and types
+    let ffi_src = Path::new(&crate_dir).join("src/ffi/mod.rs");
+    println!("cargo:rerun-if-changed={}", ffi_src.display());
+
     cbindgen::Builder::new()
-        .with_crate(crate_dir)
+        .with_src(ffi_src)
         .with_language(cbindgen::Language::C)
         .generate()
         .expect("Unable to generate bindings")
         .write_to_file("outpost_core.h");
-}
\ No newline at end of file
+}
@@ -15,13 +15,18 @@ typedef struct GameStateHandle {
  */
 struct GameStateHandle *game_state_new(void);
 
+/**
+ * Create new game state with an explicit game seed
+ */
+struct GameStateHandle *game_state_new_seeded(uint64_t seed);
+
 /**
  * Free game state
  */
 void game_state_free(struct GameStateHandle *handle);
 
 /**
- * Apply command (returns JSON events)
+ * Apply command (returns JSON event envelopes, or a JSON `CommandRejected` object)
  * Caller must free returned string with game_string_free
  */
 char *game_state_apply_command(struct GameStateHandle *handle, const char *command_json);
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display as StrumDisplay, EnumIter};
+
+use crate::travel::DriveProfile;
+
+/// Probe designs the player can launch
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, EnumIter, StrumDisplay, Serialize, Deserialize,
+)]
+pub enum ProbeClass {
+    /// Fast and cheap, but only resolves the star itself
+    Scout,
+    /// Resolves planets but not asteroid belts
+    #[default]
+    Survey,
+    /// Slow, long-ranged and expensive, resolves every body
+    DeepSurvey,
+}
+
+/// Performance figures of a probe class
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProbeSpec {
+    pub drive: DriveProfile,
+    /// Furthest a probe can be sent from its origin, in light-years
+    pub max_range_ly: f64,
+    /// Sensor quality from 0 to 1; higher resolves fainter bodies
+    pub sensor_resolution: f64,
+    /// Expected damaging or fatal incidents per year of the probe's own time
+    pub incidents_per_year: f64,
+    /// Build cost in credits
+    pub cost: u32,
+}
+
+impl ProbeClass {
+    pub fn spec(self) -> ProbeSpec {
+        match self {
+            ProbeClass::Scout => ProbeSpec {
+                drive: DriveProfile {
+                    acceleration_g: 1.0,
+                    max_speed_c: 0.2,
+                    deceleration_g: 1.0,
+                },
+                max_range_ly: 30.0,
+                sensor_resolution: 0.3,
+                incidents_per_year: 0.005,
+                cost: 50,
+            },
+            ProbeClass::Survey => ProbeSpec {
+                drive: DriveProfile {
+                    acceleration_g: 0.5,
+                    max_speed_c: 0.1,
+                    deceleration_g: 0.5,
+                },
+                max_range_ly: 60.0,
+                sensor_resolution: 0.6,
+                incidents_per_year: 0.002,
+                cost: 120,
+            },
+            ProbeClass::DeepSurvey => ProbeSpec {
+                drive: DriveProfile {
+                    acceleration_g: 0.2,
+                    max_speed_c: 0.05,
+                    deceleration_g: 0.2,
+                },
+                max_range_ly: 150.0,
+                sensor_resolution: 0.9,
+                incidents_per_year: 0.001,
+                cost: 300,
+            },
+        }
+    }
+}
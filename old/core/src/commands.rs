@@ -1,12 +1,49 @@
 use serde::{Deserialize, Serialize};
 
-use crate::StarSystemId;
+use crate::{CelestialBodyId, ProbeClass, ProbeId, StarSystemId};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[repr(C)]
 #[serde(tag = "type")]
 pub enum Command {
-    AdvanceTime { dt: f64 },
-    LaunchProbe { target_system_id: StarSystemId},
+    AdvanceTime {
+        dt: f64,
+    },
+    /// Launch from `origin_system_id`, a discovered system, or from Sol if
+    /// `None`. The probe scans `target_system_id`, then each waypoint in turn
+    /// while its range lasts.
+    LaunchProbe {
+        target_system_id: StarSystemId,
+        #[serde(default)]
+        origin_system_id: Option<StarSystemId>,
+        #[serde(default)]
+        probe_class: ProbeClass,
+        #[serde(default)]
+        waypoints: Vec<StarSystemId>,
+    },
+    /// Send a probe in flight to a different system instead
+    RedirectProbe {
+        probe_id: ProbeId,
+        target_system_id: StarSystemId,
+    },
+    /// Send a probe in flight back to the system it was launched from
+    RecallProbe {
+        probe_id: ProbeId,
+    },
+    /// Survey a scanned system in full, taking `EXPLORATION_DURATION_HOURS`
+    ExploreSystem {
+        system_id: StarSystemId,
+    },
+    InitializeGalaxy {
+        seed: u64,
+        star_count: u32,
+    },
+    /// Rename a star system, or one of its known bodies or belts if `body_id`
+    /// is given. Names are unique across the galaxy.
+    RenameEntity {
+        system_id: StarSystemId,
+        #[serde(default)]
+        body_id: Option<CelestialBodyId>,
+        name: String,
+    },
 }
-
@@ -1,7 +1,13 @@
-use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use strum::{Display as StrumDisplay, EnumIter};
 use ulid::Ulid;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+use crate::catalog::ProbeClass;
+use crate::schedule::{Schedule, ScheduledAction};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct StarSystemId(pub Ulid);
 impl StarSystemId {
     pub fn new() -> Self {
@@ -9,7 +15,7 @@ impl StarSystemId {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ProbeId(pub Ulid);
 impl ProbeId {
     pub fn new() -> Self {
@@ -29,70 +35,642 @@ pub fn generate_id() -> Ulid {
     Ulid::new()
 }
 
+/// Distance in AU beyond which water ice condensed in the protoplanetary disk
+/// of a star, or group of stars, of `luminosity` relative to Sol, letting
+/// giant planets form
+pub fn frost_line_au(luminosity: f64) -> f64 {
+    2.7 * luminosity.sqrt()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "SavedGameState")]
 pub struct GameState {
+    pub seed: u64,
     pub game_time: f64,
-    pub systems: Vec<StarSystem>,
-    pub probes_in_flight: Vec<ProbeInFlight>,
+    #[serde(with = "keyed_list")]
+    pub systems: BTreeMap<StarSystemId, StarSystem>,
+    /// Set once the galaxy is initialized
+    pub sol_id: Option<StarSystemId>,
+    #[serde(with = "keyed_list")]
+    pub probes_in_flight: BTreeMap<ProbeId, ProbeInFlight>,
+    /// Scan results on their way back to Sol at light speed
+    #[serde(with = "keyed_list")]
+    pub data_in_transit: BTreeMap<StarSystemId, DataInTransit>,
+    pub schedule: Schedule,
+    /// Derived from `systems` and `data_in_transit`, so both must only change
+    /// through the `with_*` methods
+    #[serde(skip)]
+    names: NameIndex,
+}
+
+/// `GameState` as saved, without the index derived from it
+#[derive(Deserialize)]
+struct SavedGameState {
+    seed: u64,
+    game_time: f64,
+    #[serde(with = "keyed_list")]
+    systems: BTreeMap<StarSystemId, StarSystem>,
+    sol_id: Option<StarSystemId>,
+    #[serde(with = "keyed_list")]
+    probes_in_flight: BTreeMap<ProbeId, ProbeInFlight>,
+    #[serde(with = "keyed_list")]
+    data_in_transit: BTreeMap<StarSystemId, DataInTransit>,
+    schedule: Schedule,
+}
+
+impl From<SavedGameState> for GameState {
+    fn from(saved: SavedGameState) -> Self {
+        let mut state = Self {
+            seed: saved.seed,
+            game_time: saved.game_time,
+            systems: saved.systems,
+            sol_id: saved.sol_id,
+            probes_in_flight: saved.probes_in_flight,
+            data_in_transit: saved.data_in_transit,
+            schedule: saved.schedule,
+            names: NameIndex::default(),
+        };
+        state.index_names();
+        state
+    }
+}
+
+/// How many star systems, bodies and belts go by each name: all of them, and
+/// those the player knows about. Scan data in transit is not known yet.
+#[derive(Debug, Clone, Default)]
+struct NameIndex {
+    counts: HashMap<String, NameCount>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct NameCount {
+    all: u32,
+    known: u32,
+}
+
+impl NameIndex {
+    fn insert(&mut self, system: &StarSystem, in_transit: bool) {
+        for (name, known) in system_names(system, in_transit) {
+            let count = self.counts.entry(name.to_string()).or_default();
+            count.all += 1;
+            count.known += u32::from(known);
+        }
+    }
+
+    fn remove(&mut self, system: &StarSystem, in_transit: bool) {
+        for (name, known) in system_names(system, in_transit) {
+            let Some(count) = self.counts.get_mut(name) else {
+                continue;
+            };
+            count.all -= 1;
+            count.known -= u32::from(known);
+            if count.all == 0 {
+                self.counts.remove(name);
+            }
+        }
+    }
+
+    fn get(&self, name: &str) -> NameCount {
+        self.counts.get(name).copied().unwrap_or_default()
+    }
+}
+
+// Every name in `system`, with whether the player knows of it
+fn system_names(system: &StarSystem, in_transit: bool) -> impl Iterator<Item = (&str, bool)> {
+    let bodies = system
+        .bodies
+        .iter()
+        .map(|body| (body.name.as_str(), body.discovery_level));
+    let belts = system
+        .belts
+        .iter()
+        .map(|belt| (belt.name.as_str(), belt.discovery_level));
+    std::iter::once((system.name.as_str(), system.discovery_level))
+        .chain(bodies)
+        .chain(belts)
+        .map(move |(name, level)| (name, !in_transit && level > DiscoveryLevel::Unknown))
+}
+
+/// Position in light-years, with Sol at the origin
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Position {
+    pub const ORIGIN: Position = Position {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+
+    pub fn distance_to(&self, other: &Position) -> f64 {
+        let (dx, dy, dz) = (self.x - other.x, self.y - other.y, self.z - other.z);
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+
+    /// Point a fraction `t` of the way from `self` to `other`
+    pub fn lerp(&self, other: &Position, t: f64) -> Position {
+        Position {
+            x: self.x + (other.x - self.x) * t,
+            y: self.y + (other.y - self.y) * t,
+            z: self.z + (other.z - self.z) * t,
+        }
+    }
+}
+
+#[derive(
+    EnumIter, Debug, Clone, Copy, StrumDisplay, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
+pub enum SpectralClass {
+    O,
+    B,
+    A,
+    F,
+    G,
+    K,
+    M,
+}
+
+#[derive(
+    EnumIter, Debug, Clone, Copy, StrumDisplay, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
+pub enum CelestialBodyType {
+    Star,
+    Planet,
+    Moon,
+}
+
+/// What a planet, moon or asteroid belt is mostly made of
+#[derive(
+    EnumIter, Debug, Clone, Copy, StrumDisplay, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
+pub enum Composition {
+    Rocky,
+    GasGiant,
+    IceGiant,
+    Icy,
+}
+
+/// How much the player knows about a star system or body
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DiscoveryLevel {
+    /// Not known to exist
+    Unknown,
+    /// Position and name known, no details
+    Detected,
+    /// Surveyed by a probe: star characteristics and the larger bodies known
+    Scanned,
+    /// Fully explored, every body revealed
+    Explored,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StarSystem {
     pub id: StarSystemId,
     pub name: String,
-    pub spectral_class: String,
+    pub spectral_class: SpectralClass,
+    pub position: Position,
+    pub distance_from_sol: f64,
+    /// Mass of the primary star relative to Sol
+    pub mass: f64,
+    /// Luminosity of the primary star relative to Sol
+    pub luminosity: f64,
+    /// Effective surface temperature of the primary star in kelvin
+    pub temperature: f64,
+    pub discovery_level: DiscoveryLevel,
     pub bodies: Vec<CelestialBody>,
+    /// Empty if none or not yet generated
+    pub belts: Vec<AsteroidBelt>,
+    /// `None` until the system is generated
+    pub oort_cloud: Option<OortCloud>,
+}
+
+impl StarSystem {
+    /// A system is discovered once a probe has scanned it
+    pub fn is_discovered(&self) -> bool {
+        self.discovery_level >= DiscoveryLevel::Scanned
+    }
+
+    /// Bodies the player knows about
+    pub fn known_bodies(&self) -> impl Iterator<Item = &CelestialBody> {
+        self.bodies
+            .iter()
+            .filter(|body| body.discovery_level > DiscoveryLevel::Unknown)
+    }
+
+    /// Belts the player knows about
+    pub fn known_belts(&self) -> impl Iterator<Item = &AsteroidBelt> {
+        self.belts
+            .iter()
+            .filter(|belt| belt.discovery_level > DiscoveryLevel::Unknown)
+    }
+
+    /// Inner and outer edge of the primary star's zone where liquid surface
+    /// water is possible, in AU, using the runaway and maximum greenhouse limits
+    pub fn habitable_zone_au(&self) -> (f64, f64) {
+        (
+            (self.luminosity / 1.1).sqrt(),
+            (self.luminosity / 0.53).sqrt(),
+        )
+    }
+
+    pub fn in_habitable_zone(&self, orbit_au: f64) -> bool {
+        let (inner, outer) = self.habitable_zone_au();
+        (inner..=outer).contains(&orbit_au)
+    }
+
+    pub fn body(&self, body_id: CelestialBodyId) -> Option<&CelestialBody> {
+        self.bodies.iter().find(|body| body.id == body_id)
+    }
+
+    /// The primary star every other body ultimately orbits
+    pub fn root_star(&self) -> Option<&CelestialBody> {
+        self.bodies.iter().find(|body| body.parent_id.is_none())
+    }
+
+    /// Rename the system, and its primary star along with it while the star
+    /// goes by the system's name
+    pub fn rename(&mut self, name: &str) {
+        let old_name = std::mem::replace(&mut self.name, name.to_string());
+        if let Some(star) = self
+            .bodies
+            .iter_mut()
+            .find(|body| body.parent_id.is_none() && body.name == old_name)
+        {
+            star.name = name.to_string();
+        }
+    }
+
+    /// The primary star followed by any companions
+    pub fn stars(&self) -> impl Iterator<Item = &CelestialBody> {
+        self.bodies
+            .iter()
+            .filter(|body| body.body_type == CelestialBodyType::Star)
+    }
+
+    /// Bodies directly orbiting `body_id`
+    pub fn children(&self, body_id: CelestialBodyId) -> impl Iterator<Item = &CelestialBody> {
+        self.bodies
+            .iter()
+            .filter(move |body| body.parent_id == Some(body_id))
+    }
+
+    /// The body followed by each of its parents in turn, ending at the root
+    /// star; empty if the body is not in this system. Parents that loop back,
+    /// as only a corrupt save could hold, end the path once it is as long as
+    /// the body list.
+    pub fn path_to_root(&self, body_id: CelestialBodyId) -> Vec<&CelestialBody> {
+        let mut path = Vec::new();
+        let mut next = self.body(body_id);
+        while let Some(body) = next
+            && path.len() < self.bodies.len()
+        {
+            path.push(body);
+            next = body.parent_id.and_then(|parent_id| self.body(parent_id));
+        }
+        path
+    }
+
+    /// The primary star's frost line, in AU
+    pub fn frost_line_au(&self) -> f64 {
+        frost_line_au(self.luminosity)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProbeInFlight {
     pub id: ProbeId,
+    pub probe_class: ProbeClass,
     pub target_system_id: StarSystemId,
+    /// System the probe was launched from; `None` for Sol
+    pub origin_system_id: Option<StarSystemId>,
     pub launched_at: f64,
+    /// Where and when the current leg started: the launch, or the last course
+    /// change
+    pub departure: Position,
+    pub departed_at: f64,
+    /// Length of the current leg
+    pub distance_ly: f64,
     pub arrival_time: f64,
+    /// Duration of the current leg as measured on board, shorter than the
+    /// galaxy-frame `arrival_time - departed_at` by time dilation
+    pub ship_frame_hours: f64,
+    /// Remaining fraction of sensor output, 1 for an undamaged probe
+    pub condition: f64,
+    /// Systems still to visit after `target_system_id`, in order
+    pub waypoints: Vec<StarSystemId>,
+    /// Distance covered by finished or abandoned legs, counted against the
+    /// class's range
+    pub distance_flown_ly: f64,
+}
+
+impl ProbeInFlight {
+    /// Range left for a new leg starting at `position` on the current leg
+    pub fn range_left_ly(&self, position: &Position) -> f64 {
+        self.probe_class.spec().max_range_ly
+            - self.distance_flown_ly
+            - self.departure.distance_to(position)
+    }
+}
+
+/// A probe's scan of `system`, transmitted when it arrived and known at home
+/// once the signal reaches Sol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataInTransit {
+    pub system: StarSystem,
+    pub transmitted_at: f64,
+    pub received_at: f64,
+}
+
+/// A new leg for a probe in flight, starting from rest at `departure`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbeCourse {
+    pub target_system_id: StarSystemId,
+    pub departure: Position,
+    pub distance_ly: f64,
+    pub eta: f64,
+    pub ship_frame_hours: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CelestialBody {
     pub id: CelestialBodyId,
     pub name: String,
-    pub body_type: String,
+    pub body_type: CelestialBodyType,
+    /// Body this one orbits; `None` for the primary star at the root of the
+    /// system. A companion star orbits the primary together with every
+    /// companion closer in, and so does a planet circling several stars.
+    pub parent_id: Option<CelestialBodyId>,
+    pub discovery_level: DiscoveryLevel,
+    /// Mass in Earth masses
+    pub mass: f64,
+    /// Radius in Earth radii
+    pub radius: f64,
+    /// Orbital distance from the parent in AU, 0 for the primary star
+    pub orbit_au: f64,
+    /// `None` for stars
+    pub composition: Option<Composition>,
+    /// Surface temperature in kelvin; the photosphere for a star
+    pub surface_temperature: f64,
+}
+
+/// A ring of debris around the star, such as the asteroid or Kuiper belt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsteroidBelt {
+    pub id: CelestialBodyId,
+    pub name: String,
+    /// Star the belt circles, or the primary for a belt around several stars
+    pub parent_id: CelestialBodyId,
+    pub discovery_level: DiscoveryLevel,
+    pub inner_radius_au: f64,
+    pub outer_radius_au: f64,
+    /// Amount of material relative to the Solar System's main belt
+    pub density: f64,
+    pub composition: Composition,
+}
+
+/// The shell of icy bodies loosely bound to the star, marking the outer edge
+/// of the system
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct OortCloud {
+    pub radius_au: f64,
 }
 
 impl GameState {
     pub fn new() -> Self {
         Self {
+            seed: 0,
             game_time: 0.0,
-            systems: Vec::new(),
-            probes_in_flight: Vec::new(),
+            systems: BTreeMap::new(),
+            sol_id: None,
+            probes_in_flight: BTreeMap::new(),
+            data_in_transit: BTreeMap::new(),
+            schedule: Schedule::new(),
+            names: NameIndex::default(),
         }
     }
 
-    pub fn with_probe_launched(
-        mut self,
-        target_system_id: StarSystemId,
-        arrival_time: f64,
-    ) -> (Self, ProbeId) {
-        let probe_id = ProbeId(Ulid::new());
-        self.probes_in_flight.push(ProbeInFlight {
-            id: probe_id,
-            target_system_id: target_system_id,
-            launched_at: self.game_time,
-            arrival_time,
-        });
-        (self, probe_id)
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_game_time(mut self, game_time: f64) -> Self {
+        self.game_time = game_time;
+        self
+    }
+
+    pub fn with_probe_launched(mut self, probe: ProbeInFlight) -> Self {
+        self.schedule.push(
+            probe.arrival_time,
+            ScheduledAction::ProbeArrival { probe_id: probe.id },
+        );
+        self.probes_in_flight.insert(probe.id, probe);
+        self
+    }
+
+    pub fn with_probe_course_changed(mut self, probe_id: ProbeId, course: &ProbeCourse) -> Self {
+        let Some(probe) = self.probes_in_flight.get_mut(&probe_id) else {
+            return self;
+        };
+        probe.distance_flown_ly += probe.departure.distance_to(&course.departure);
+        probe.target_system_id = course.target_system_id;
+        probe.departure = course.departure;
+        probe.departed_at = self.game_time;
+        probe.distance_ly = course.distance_ly;
+        probe.arrival_time = course.eta;
+        probe.ship_frame_hours = course.ship_frame_hours;
+        self.schedule
+            .push(course.eta, ScheduledAction::ProbeArrival { probe_id });
+        self
+    }
+
+    /// Continue to the next waypoint
+    pub fn with_probe_departed(self, probe_id: ProbeId, course: &ProbeCourse) -> Self {
+        let mut state = self.with_probe_course_changed(probe_id, course);
+        if let Some(probe) = state.probes_in_flight.get_mut(&probe_id) {
+            probe.waypoints.remove(0);
+        }
+        state
+    }
+
+    pub fn with_probe_recalled(self, probe_id: ProbeId, course: &ProbeCourse) -> Self {
+        let mut state = self.with_probe_course_changed(probe_id, course);
+        if let Some(probe) = state.probes_in_flight.get_mut(&probe_id) {
+            probe.waypoints.clear();
+        }
+        state
+    }
+
+    /// A probe that reaches its last stop is done; one with waypoints left
+    /// waits at the system until it departs or is retired.
+    pub fn with_probe_arrived(mut self, probe_id: ProbeId) -> Self {
+        let Some(probe) = self.probes_in_flight.get_mut(&probe_id) else {
+            return self;
+        };
+        if probe.waypoints.is_empty() {
+            return self.with_probes_removed(&[probe_id]);
+        }
+
+        let arrived_at = self
+            .systems
+            .get(&probe.target_system_id)
+            .map_or(probe.departure, |system| system.position);
+        probe.distance_flown_ly += probe.distance_ly;
+        probe.departure = arrived_at;
+        probe.departed_at = self.game_time;
+        probe.distance_ly = 0.0;
+        probe.arrival_time = self.game_time;
+        probe.ship_frame_hours = 0.0;
+        self.schedule
+            .remove(&ScheduledAction::ProbeArrival { probe_id });
+        self
+    }
+
+    pub fn with_probe_damaged(mut self, probe_id: ProbeId, condition: f64) -> Self {
+        if let Some(probe) = self.probes_in_flight.get_mut(&probe_id) {
+            probe.condition = condition;
+        }
+        self
+    }
+
+    pub fn sol(&self) -> Option<&StarSystem> {
+        self.sol_id.and_then(|sol_id| self.systems.get(&sol_id))
+    }
+
+    /// Whether any star system, body or belt already goes by `name`,
+    /// including those not found yet and those in scan data still on its way
+    /// to Sol
+    pub fn is_name_taken(&self, name: &str) -> bool {
+        self.names.get(name).all > 0
+    }
+
+    /// Whether a star system, body or belt the player knows about goes by
+    /// `name`
+    pub fn is_name_known(&self, name: &str) -> bool {
+        self.names.get(name).known > 0
+    }
+
+    fn index_names(&mut self) {
+        self.names = NameIndex::default();
+        for system in self.systems.values() {
+            self.names.insert(system, false);
+        }
+        for data in self.data_in_transit.values() {
+            self.names.insert(&data.system, true);
+        }
+    }
+
+    // Change a known system, keeping the name index in step
+    fn update_system(&mut self, system_id: StarSystemId, change: impl FnOnce(&mut StarSystem)) {
+        if let Some(system) = self.systems.get_mut(&system_id) {
+            self.names.remove(system, false);
+            change(system);
+            self.names.insert(system, false);
+        }
+    }
+
+    /// Galaxy generation always lists Sol first
+    pub fn with_galaxy_initialized(mut self, systems: &[StarSystem]) -> Self {
+        self.sol_id = systems.first().map(|sol| sol.id);
+        self.systems = systems
+            .iter()
+            .map(|system| (system.id, system.clone()))
+            .collect();
+        self.index_names();
+        self
     }
 
-    pub fn with_system_discovered(mut self, system: StarSystem) -> Self {
-        if !self.systems.iter().any(|s| s.id == system.id) {
-            self.systems.push(system);
+    pub fn with_discovery_data_transmitted(mut self, system: StarSystem, received_at: f64) -> Self {
+        let system_id = system.id;
+        self.schedule.push(
+            received_at,
+            ScheduledAction::DiscoveryDataArrival { system_id },
+        );
+        self.names.insert(&system, true);
+        let data = DataInTransit {
+            system,
+            transmitted_at: self.game_time,
+            received_at,
+        };
+        if let Some(replaced) = self.data_in_transit.insert(system_id, data) {
+            self.names.remove(&replaced.system, true);
+        }
+        self
+    }
+
+    /// A name the player gave the system while the data was in transit is
+    /// kept, and passed on to its star
+    pub fn with_discovery_data_received(mut self, mut system: StarSystem) -> Self {
+        let system_id = system.id;
+        if let Some(data) = self.data_in_transit.remove(&system_id) {
+            self.names.remove(&data.system, true);
+        }
+        self.schedule
+            .remove(&ScheduledAction::DiscoveryDataArrival { system_id });
+        if let Some(known) = self.systems.get(&system_id) {
+            system.rename(&known.name);
+        }
+        self.names.insert(&system, false);
+        if let Some(replaced) = self.systems.insert(system_id, system) {
+            self.names.remove(&replaced, false);
         }
         self
     }
 
+    pub fn with_exploration_started(mut self, system_id: StarSystemId, completes_at: f64) -> Self {
+        self.schedule.push(
+            completes_at,
+            ScheduledAction::SystemExploration { system_id },
+        );
+        self
+    }
+
+    pub fn with_system_explored(mut self, system_id: StarSystemId) -> Self {
+        self.update_system(system_id, |system| {
+            system.discovery_level = DiscoveryLevel::Explored;
+            for body in &mut system.bodies {
+                body.discovery_level = DiscoveryLevel::Explored;
+            }
+            for belt in &mut system.belts {
+                belt.discovery_level = DiscoveryLevel::Explored;
+            }
+        });
+        self.schedule
+            .remove(&ScheduledAction::SystemExploration { system_id });
+        self
+    }
+
+    pub fn with_entity_renamed(
+        mut self,
+        system_id: StarSystemId,
+        body_id: Option<CelestialBodyId>,
+        name: &str,
+    ) -> Self {
+        self.update_system(system_id, |system| {
+            let Some(body_id) = body_id else {
+                system.rename(name);
+                return;
+            };
+            if let Some(body) = system.bodies.iter_mut().find(|body| body.id == body_id) {
+                body.name = name.to_string();
+            } else if let Some(belt) = system.belts.iter_mut().find(|belt| belt.id == body_id) {
+                belt.name = name.to_string();
+            }
+        });
+        self
+    }
+
     pub fn with_probes_removed(mut self, probe_ids: &[ProbeId]) -> Self {
-        self.probes_in_flight
-            .retain(|probe| !probe_ids.contains(&probe.id));
+        for probe_id in probe_ids {
+            self.probes_in_flight.remove(probe_id);
+            self.schedule.remove(&ScheduledAction::ProbeArrival {
+                probe_id: *probe_id,
+            });
+        }
         self
     }
 }
@@ -102,3 +680,56 @@ impl Default for GameState {
         Self::new()
     }
 }
+
+/// Values stored in an id-keyed map of `GameState`.
+pub trait Keyed {
+    type Key: Ord;
+    fn key(&self) -> Self::Key;
+}
+
+impl Keyed for StarSystem {
+    type Key = StarSystemId;
+    fn key(&self) -> StarSystemId {
+        self.id
+    }
+}
+
+impl Keyed for DataInTransit {
+    type Key = StarSystemId;
+    fn key(&self) -> StarSystemId {
+        self.system.id
+    }
+}
+
+impl Keyed for ProbeInFlight {
+    type Key = ProbeId;
+    fn key(&self) -> ProbeId {
+        self.id
+    }
+}
+
+/// Serializes an id-keyed map as a list of its values ordered by id, so saved
+/// documents keep the same shape they had as a `Vec`.
+mod keyed_list {
+    use super::*;
+
+    pub fn serialize<S, V>(map: &BTreeMap<V::Key, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        V: Keyed + Serialize,
+    {
+        serializer.collect_seq(map.values())
+    }
+
+    pub fn deserialize<'de, D, V>(deserializer: D) -> Result<BTreeMap<V::Key, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+        V: Keyed + Deserialize<'de>,
+    {
+        let values = Vec::<V>::deserialize(deserializer)?;
+        Ok(values
+            .into_iter()
+            .map(|value| (value.key(), value))
+            .collect())
+    }
+}
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{CelestialBodyId, Command, DiscoveryLevel, ProbeClass, ProbeId, StarSystemId};
+
+/// Reasons a command can be refused by the reducer. A rejected command
+/// leaves the state untouched and produces no events.
+#[derive(Debug, Clone, PartialEq, Error, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum CommandError {
+    #[error("unknown star system {}", system_id.0)]
+    UnknownSystem { system_id: StarSystemId },
+    #[error("unknown body {} in star system {}", body_id.0, system_id.0)]
+    UnknownBody {
+        system_id: StarSystemId,
+        body_id: CelestialBodyId,
+    },
+    #[error("unknown probe {}", probe_id.0)]
+    UnknownProbe { probe_id: ProbeId },
+    #[error("probe {} is already heading to star system {}", probe_id.0, system_id.0)]
+    AlreadyEnRoute {
+        probe_id: ProbeId,
+        system_id: StarSystemId,
+    },
+    #[error("probe {} has no system to return to", probe_id.0)]
+    NoReturnSystem { probe_id: ProbeId },
+    #[error("invalid time step {dt}: must be a finite, non-negative number of hours")]
+    InvalidDuration { dt: f64 },
+    #[error("cannot launch from star system {}: it must be discovered and not the target", system_id.0)]
+    InvalidOrigin { system_id: StarSystemId },
+    #[error("cannot launch to star system {}: the probe would start there", system_id.0)]
+    TargetIsOrigin { system_id: StarSystemId },
+    #[error("star system {} is {distance_ly:.1} ly away, beyond the {max_range_ly} ly range of a {probe_class} probe", system_id.0)]
+    OutOfRange {
+        system_id: StarSystemId,
+        probe_class: ProbeClass,
+        distance_ly: f64,
+        max_range_ly: f64,
+    },
+    #[error("probe {} has {range_left_ly:.1} ly of range left, not the {distance_ly:.1} ly to star system {}", probe_id.0, system_id.0)]
+    RangeExhausted {
+        probe_id: ProbeId,
+        system_id: StarSystemId,
+        distance_ly: f64,
+        range_left_ly: f64,
+    },
+    #[error("star system {} follows itself in the itinerary", system_id.0)]
+    RepeatedWaypoint { system_id: StarSystemId },
+    #[error("a probe is already en route to star system {}", system_id.0)]
+    DuplicateProbe { system_id: StarSystemId },
+    #[error("star system {} is {discovery_level:?}: only scanned systems can be explored", system_id.0)]
+    NotExplorable {
+        system_id: StarSystemId,
+        discovery_level: DiscoveryLevel,
+    },
+    #[error("star system {} is already being explored", system_id.0)]
+    ExplorationInProgress { system_id: StarSystemId },
+    #[error("the galaxy has already been initialized")]
+    GalaxyAlreadyInitialized,
+    #[error(
+        "invalid star count {star_count}: the galaxy holds 1 to {} stars",
+        crate::systems::galaxy::MAX_STAR_COUNT
+    )]
+    InvalidStarCount { star_count: u32 },
+    #[error("names must not be blank")]
+    BlankName,
+    #[error("the name {name:?} is already taken")]
+    NameTaken { name: String },
+}
+
+/// Outcome handed to frontends when a command is refused, so the reason can
+/// be shown to the player.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandRejected {
+    pub command: Command,
+    pub error: CommandError,
+    pub message: String,
+}
+
+impl CommandRejected {
+    pub fn new(command: Command, error: CommandError) -> Self {
+        let message = error.to_string();
+        Self {
+            command,
+            error,
+            message,
+        }
+    }
+}
@@ -1,6 +1,27 @@
 use serde::{Deserialize, Serialize};
+use ulid::Ulid;
 
-use crate::{ProbeId, StarSystem, StarSystemId};
+use crate::{CelestialBodyId, ProbeClass, ProbeCourse, ProbeId, StarSystem, StarSystemId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EventId(pub Ulid);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CommandId(pub Ulid);
+
+/// A payload stamped with its position in the event log.
+///
+/// `offset` is strictly increasing across the whole log and is the ordering
+/// key for persistence and UI timelines; `game_time` is when the event
+/// happened in the simulation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    pub offset: u64,
+    pub game_time: f64,
+    pub event_id: EventId,
+    pub command_id: CommandId,
+    pub payload: EventPayload,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EventPayload {
@@ -10,14 +31,78 @@ pub enum EventPayload {
     },
     ProbeLaunched {
         probe_id: ProbeId,
+        probe_class: ProbeClass,
         target_system_id: StarSystemId,
+        /// `None` when launched from Sol
+        origin_system_id: Option<StarSystemId>,
+        distance_ly: f64,
         eta: f64,
+        /// Flight time on the probe's own clock
+        ship_frame_hours: f64,
+        /// Systems to visit after the target, in order
+        waypoints: Vec<StarSystemId>,
+    },
+    /// The probe left a scanned system for its next waypoint
+    ProbeDeparted {
+        probe_id: ProbeId,
+        course: ProbeCourse,
+    },
+    /// The probe was taken out of service at `system_id`, with waypoints left
+    /// that it no longer has the range to reach
+    ProbeRetired {
+        probe_id: ProbeId,
+        system_id: StarSystemId,
+    },
+    /// The player sent the probe to a different system mid-flight
+    ProbeRedirected {
+        probe_id: ProbeId,
+        course: ProbeCourse,
+    },
+    /// The player ordered the probe back to the system it was launched from
+    ProbeRecalled {
+        probe_id: ProbeId,
+        course: ProbeCourse,
+    },
+    /// An incident en route left the probe with `condition` sensor output
+    ProbeDamaged {
+        probe_id: ProbeId,
+        condition: f64,
+    },
+    /// The probe was destroyed en route and will never arrive
+    ProbeLost {
+        probe_id: ProbeId,
     },
     ProbeArrived {
         probe_id: ProbeId,
         system_id: StarSystemId,
     },
-    SystemDiscovered {
+    /// A probe scanned a detected system and sent the results home; they are
+    /// known once they arrive at `received_at`
+    DiscoveryDataTransmitted {
         system: StarSystem,
+        received_at: f64,
+    },
+    /// Scan results reached Sol, revealing the system's star and larger bodies
+    DiscoveryDataReceived {
+        system: StarSystem,
+    },
+    ExplorationStarted {
+        system_id: StarSystemId,
+        completes_at: f64,
+    },
+    /// Every body of the system is now known
+    SystemExplored {
+        system_id: StarSystemId,
+    },
+    /// The galaxy was generated from its own `seed`; the game seed is unchanged
+    GalaxyInitialized {
+        seed: u64,
+        systems: Vec<StarSystem>,
+    },
+    /// The player renamed a star system, or a body or belt in it
+    EntityRenamed {
+        system_id: StarSystemId,
+        body_id: Option<CelestialBodyId>,
+        name: String,
     },
 }
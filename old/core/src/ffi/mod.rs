@@ -9,11 +9,26 @@ pub struct GameStateHandle {
     _private: [u8; 0],
 }
 
+/// State behind a handle, plus the offset of the next event to be emitted
+struct Session {
+    state: GameState,
+    next_offset: u64,
+}
+
 /// Create new game state
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn game_state_new() -> *mut GameStateHandle {
-    let state = Box::new(GameState::new());
-    Box::into_raw(state) as *mut GameStateHandle
+    game_state_new_seeded(0)
+}
+
+/// Create new game state with an explicit game seed
+#[unsafe(no_mangle)]
+pub extern "C" fn game_state_new_seeded(seed: u64) -> *mut GameStateHandle {
+    let session = Box::new(Session {
+        state: GameState::new().with_seed(seed),
+        next_offset: 0,
+    });
+    Box::into_raw(session) as *mut GameStateHandle
 }
 
 /// Free game state
@@ -21,29 +36,35 @@ pub unsafe extern "C" fn game_state_new() -> *mut GameStateHandle {
 pub unsafe extern "C" fn game_state_free(handle: *mut GameStateHandle) {
     if !handle.is_null() {
         unsafe {
-            drop(Box::from_raw(handle as *mut GameState));
+            drop(Box::from_raw(handle as *mut Session));
         }
     }
 }
 
-/// Apply command (returns JSON events)
+/// Apply command (returns JSON event envelopes, or a JSON `CommandRejected` object)
 /// Caller must free returned string with game_string_free
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn game_state_apply_command(
     handle: *mut GameStateHandle,
     command_json: *const c_char,
 ) -> *mut c_char {
-    let state = unsafe { &mut *(handle as *mut GameState) };
+    let session = unsafe { &mut *(handle as *mut Session) };
     let json_str = unsafe { CStr::from_ptr(command_json) }.to_str().unwrap();
 
     let command: Command = serde_json::from_str(json_str).unwrap();
-    let ctx = ReducerContext {
-        current_offset: 0,
-        game_time: state.game_time,
-    };
+    let ctx = ReducerContext::new(&session.state, session.next_offset);
 
-    let (new_state, events) = systems::reduce(state.clone(), command, ctx);
-    *state = new_state;
+    let (new_state, events) = match systems::reduce(session.state.clone(), command.clone(), ctx) {
+        Ok(result) => result,
+        Err(error) => {
+            let rejected = serde_json::json!({
+                "CommandRejected": CommandRejected::new(command, error)
+            });
+            return CString::new(rejected.to_string()).unwrap().into_raw();
+        }
+    };
+    session.state = new_state;
+    session.next_offset += events.len() as u64;
 
     let events_json = serde_json::to_string(&events).unwrap();
     CString::new(events_json).unwrap().into_raw()
@@ -52,8 +73,8 @@ pub unsafe extern "C" fn game_state_apply_command(
 /// Get state as JSON
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn game_state_to_json(handle: *const GameStateHandle) -> *mut c_char {
-    let state = unsafe { &*(handle as *const GameState) };
-    let json = serde_json::to_string(state).unwrap();
+    let session = unsafe { &*(handle as *const Session) };
+    let json = serde_json::to_string(&session.state).unwrap();
     CString::new(json).unwrap().into_raw()
 }
 
@@ -1,11 +1,21 @@
+pub mod catalog;
 pub mod domain;
 pub mod systems;
 pub mod commands;
+pub mod error;
 pub mod events;
+pub mod persistence;
+pub mod rng;
+pub mod schedule;
+pub mod travel;
 
 #[cfg(feature = "ffi")]
 pub mod ffi;
 
+pub use catalog::*;
 pub use domain::*;
 pub use commands::*;
-pub use events::*;
\ No newline at end of file
+pub use error::*;
+pub use events::*;
+pub use schedule::*;
+pub use travel::*;
\ No newline at end of file
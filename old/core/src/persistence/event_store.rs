@@ -0,0 +1,335 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Lines, Write};
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use super::crc32;
+use super::schema::{self, DocumentKind};
+use crate::EventEnvelope;
+
+const SEGMENT_PREFIX: &str = "events-";
+const SEGMENT_EXTENSION: &str = "jsonl";
+
+#[derive(Debug, Error)]
+pub enum EventStoreError {
+    #[error("event store I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to serialize event: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("corrupted record in {} at line {line}: {reason}", segment.display())]
+    Corrupted {
+        segment: PathBuf,
+        line: u64,
+        reason: String,
+    },
+    #[error("expected event offset {expected}, got {found}")]
+    OffsetMismatch { expected: u64, found: u64 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EventStoreConfig {
+    /// A new segment is started once the active one would grow past this size
+    pub max_segment_bytes: u64,
+}
+
+impl Default for EventStoreConfig {
+    fn default() -> Self {
+        Self {
+            max_segment_bytes: 8 * 1024 * 1024,
+        }
+    }
+}
+
+/// Append-only event log stored as JSON Lines segments in a single directory.
+///
+/// Each record is one line of the form `offset|crc32|json`, where the json is
+/// a schema-versioned `EventEnvelope` document and the checksum covers it.
+/// Segments are named after the offset of their first record, so sorting them
+/// by name gives log order.
+pub struct FileEventStore {
+    dir: PathBuf,
+    config: EventStoreConfig,
+    next_offset: u64,
+    active: Option<ActiveSegment>,
+}
+
+struct ActiveSegment {
+    file: File,
+    len: u64,
+}
+
+impl FileEventStore {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, EventStoreError> {
+        Self::open_with_config(dir, EventStoreConfig::default())
+    }
+
+    /// Open (or create) the store in `dir`. A torn final line left by a crash
+    /// is cut off so appends continue from the last complete record.
+    pub fn open_with_config(
+        dir: impl AsRef<Path>,
+        config: EventStoreConfig,
+    ) -> Result<Self, EventStoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let mut store = Self {
+            dir,
+            config,
+            next_offset: 0,
+            active: None,
+        };
+
+        if let Some((first_offset, path)) = list_segments(&store.dir)?.pop() {
+            let (next_offset, valid_len) = recover_segment(&path, first_offset)?;
+            let file = OpenOptions::new().append(true).open(&path)?;
+            file.set_len(valid_len)?;
+            store.next_offset = next_offset;
+            store.active = Some(ActiveSegment {
+                file,
+                len: valid_len,
+            });
+        }
+
+        Ok(store)
+    }
+
+    /// Offset the next appended event must carry.
+    pub fn next_offset(&self) -> u64 {
+        self.next_offset
+    }
+
+    /// Append a batch of envelopes and flush it to disk. Offsets must continue
+    /// the log without gaps; nothing is written if they do not.
+    /// Returns the offset of the first appended event.
+    pub fn append(&mut self, events: &[EventEnvelope]) -> Result<u64, EventStoreError> {
+        let start = self.next_offset;
+        for (expected, event) in (start..).zip(events) {
+            if event.offset != expected {
+                return Err(EventStoreError::OffsetMismatch {
+                    expected,
+                    found: event.offset,
+                });
+            }
+        }
+
+        for event in events {
+            let json = schema::encode(event)?;
+            let line = format!("{}|{:08x}|{}\n", event.offset, crc32(json.as_bytes()), json);
+
+            let needs_rollover = match &self.active {
+                Some(active) => {
+                    active.len > 0 && active.len + line.len() as u64 > self.config.max_segment_bytes
+                }
+                None => true,
+            };
+            if needs_rollover {
+                self.start_segment(event.offset)?;
+            }
+
+            let active = self.active.as_mut().expect("active segment");
+            active.file.write_all(line.as_bytes())?;
+            active.len += line.len() as u64;
+            self.next_offset += 1;
+        }
+
+        if let Some(active) = &mut self.active {
+            active.file.flush()?;
+            active.file.sync_data()?;
+        }
+
+        Ok(start)
+    }
+
+    /// Iterate over every stored event with `offset >= from`, in log order.
+    pub fn read_from(&self, from: u64) -> Result<EventIter, EventStoreError> {
+        let segments = list_segments(&self.dir)?;
+        // Start at the last segment whose first record is not after `from`
+        let start = segments
+            .iter()
+            .rposition(|(first_offset, _)| *first_offset <= from)
+            .unwrap_or(0);
+
+        Ok(EventIter {
+            segments: segments
+                .into_iter()
+                .skip(start)
+                .map(|(_, path)| path)
+                .collect(),
+            current: None,
+            from,
+            failed: false,
+        })
+    }
+
+    fn start_segment(&mut self, first_offset: u64) -> Result<(), EventStoreError> {
+        if let Some(previous) = &mut self.active {
+            previous.file.flush()?;
+            previous.file.sync_data()?;
+        }
+
+        let path = segment_path(&self.dir, first_offset);
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        self.active = Some(ActiveSegment { file, len: 0 });
+        Ok(())
+    }
+}
+
+/// Streaming reader over the segments of a `FileEventStore`.
+pub struct EventIter {
+    segments: VecDeque<PathBuf>,
+    current: Option<SegmentReader>,
+    from: u64,
+    failed: bool,
+}
+
+struct SegmentReader {
+    path: PathBuf,
+    lines: Lines<BufReader<File>>,
+    line: u64,
+}
+
+impl Iterator for EventIter {
+    type Item = Result<EventEnvelope, EventStoreError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        loop {
+            if self.current.is_none() {
+                let path = self.segments.pop_front()?;
+                match File::open(&path) {
+                    Ok(file) => {
+                        self.current = Some(SegmentReader {
+                            path,
+                            lines: BufReader::new(file).lines(),
+                            line: 0,
+                        })
+                    }
+                    Err(err) => {
+                        self.failed = true;
+                        return Some(Err(err.into()));
+                    }
+                }
+            }
+
+            let reader = self.current.as_mut().expect("current segment");
+            let line = match reader.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(err)) => {
+                    self.failed = true;
+                    return Some(Err(err.into()));
+                }
+                None => {
+                    self.current = None;
+                    continue;
+                }
+            };
+            reader.line += 1;
+
+            if line.is_empty() {
+                continue;
+            }
+
+            match parse_record(&line) {
+                Ok(event) if event.offset < self.from => continue,
+                Ok(event) => return Some(Ok(event)),
+                Err(reason) => {
+                    self.failed = true;
+                    return Some(Err(EventStoreError::Corrupted {
+                        segment: reader.path.clone(),
+                        line: reader.line,
+                        reason,
+                    }));
+                }
+            }
+        }
+    }
+}
+
+fn segment_path(dir: &Path, first_offset: u64) -> PathBuf {
+    dir.join(format!(
+        "{SEGMENT_PREFIX}{first_offset:020}.{SEGMENT_EXTENSION}"
+    ))
+}
+
+/// All segment files in `dir`, sorted by the offset of their first record.
+fn list_segments(dir: &Path) -> Result<Vec<(u64, PathBuf)>, EventStoreError> {
+    let mut segments = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let first_offset = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(SEGMENT_PREFIX))
+            .and_then(|name| name.strip_suffix(&format!(".{SEGMENT_EXTENSION}")))
+            .and_then(|offset| offset.parse::<u64>().ok());
+        if let Some(first_offset) = first_offset {
+            segments.push((first_offset, path));
+        }
+    }
+    segments.sort_by_key(|(first_offset, _)| *first_offset);
+    Ok(segments)
+}
+
+/// Validate every complete record of a segment and find where the valid data
+/// ends. Bytes after the last newline are a torn write and are not counted.
+/// Returns the next offset and the length of the valid prefix.
+fn recover_segment(path: &Path, first_offset: u64) -> Result<(u64, u64), EventStoreError> {
+    let bytes = fs::read(path)?;
+    let valid_len = bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+
+    let mut next_offset = first_offset;
+    for (index, line) in bytes[..valid_len].split(|&b| b == b'\n').enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+
+        let corrupted = |reason: String| EventStoreError::Corrupted {
+            segment: path.to_path_buf(),
+            line: index as u64 + 1,
+            reason,
+        };
+        let line = std::str::from_utf8(line).map_err(|err| corrupted(err.to_string()))?;
+        let event = parse_record(line).map_err(corrupted)?;
+        if event.offset != next_offset {
+            return Err(corrupted(format!(
+                "expected offset {next_offset}, found {}",
+                event.offset
+            )));
+        }
+        next_offset += 1;
+    }
+
+    Ok((next_offset, valid_len as u64))
+}
+
+fn parse_record(line: &str) -> Result<EventEnvelope, String> {
+    let mut parts = line.splitn(3, '|');
+    let (Some(offset), Some(checksum), Some(json)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err("expected `offset|checksum|json`".to_string());
+    };
+
+    let offset: u64 = offset
+        .parse()
+        .map_err(|_| format!("invalid offset `{offset}`"))?;
+    let checksum =
+        u32::from_str_radix(checksum, 16).map_err(|_| format!("invalid checksum `{checksum}`"))?;
+    if crc32(json.as_bytes()) != checksum {
+        return Err("checksum mismatch".to_string());
+    }
+
+    let event: EventEnvelope =
+        schema::decode(DocumentKind::Event, json).map_err(|err| err.to_string())?;
+    if event.offset != offset {
+        return Err(format!(
+            "line offset {offset} does not match event offset {}",
+            event.offset
+        ));
+    }
+    Ok(event)
+}
@@ -0,0 +1,19 @@
+pub mod event_store;
+pub mod schema;
+pub mod snapshot_store;
+
+pub use event_store::*;
+pub use snapshot_store::*;
+
+/// CRC-32 (IEEE 802.3) of `bytes`, used to detect torn or corrupted records.
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
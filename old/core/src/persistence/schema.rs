@@ -0,0 +1,531 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+use thiserror::Error;
+
+/// Version written into every saved document. Bump it together with a new
+/// entry in `MIGRATIONS` whenever a persisted type changes shape.
+pub const CURRENT_SCHEMA_VERSION: u32 = 17;
+
+/// What a saved document contains, so migrations know where to look.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    /// An `EventEnvelope` from the event log
+    Event,
+    /// A `Snapshot` of `GameState`
+    Snapshot,
+}
+
+#[derive(Debug, Error)]
+pub enum SchemaError {
+    #[error("schema version {found} is newer than the supported version {CURRENT_SCHEMA_VERSION}")]
+    UnsupportedVersion { found: u64 },
+    #[error("malformed document: {0}")]
+    Malformed(&'static str),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Upgrades a document's data one version in place.
+type Migration = fn(DocumentKind, &mut Value) -> Result<(), SchemaError>;
+
+/// `MIGRATIONS[n]` upgrades data from version `n` to version `n + 1`.
+const MIGRATIONS: [Migration; CURRENT_SCHEMA_VERSION as usize] = [
+    v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5, v5_to_v6, v6_to_v7, v7_to_v8, v8_to_v9,
+    v9_to_v10, v10_to_v11, v11_to_v12, v12_to_v13, v13_to_v14, v14_to_v15, v15_to_v16, v16_to_v17,
+];
+
+#[derive(Serialize)]
+struct Document<'a, T> {
+    schema_version: u32,
+    data: &'a T,
+}
+
+/// Serialize `data` wrapped in a document tagged with the current version.
+pub fn encode<T: Serialize>(data: &T) -> Result<String, serde_json::Error> {
+    serde_json::to_string(&Document {
+        schema_version: CURRENT_SCHEMA_VERSION,
+        data,
+    })
+}
+
+/// Parse a saved document, running every migration between its version and
+/// the current one before deserializing into `T`.
+pub fn decode<T: DeserializeOwned>(kind: DocumentKind, json: &str) -> Result<T, SchemaError> {
+    let (version, mut data) = split_document(serde_json::from_str(json)?)?;
+    if version > CURRENT_SCHEMA_VERSION as u64 {
+        return Err(SchemaError::UnsupportedVersion { found: version });
+    }
+
+    for migrate in &MIGRATIONS[version as usize..] {
+        migrate(kind, &mut data)?;
+    }
+    Ok(serde_json::from_value(data)?)
+}
+
+fn split_document(document: Value) -> Result<(u64, Value), SchemaError> {
+    match document {
+        Value::Object(mut fields) if fields.contains_key("schema_version") => {
+            let version = fields["schema_version"]
+                .as_u64()
+                .ok_or(SchemaError::Malformed(
+                    "schema_version is not an unsigned integer",
+                ))?;
+            let data = fields
+                .remove("data")
+                .ok_or(SchemaError::Malformed("missing data"))?;
+            Ok((version, data))
+        }
+        // Documents written before versioning are the bare data
+        data => Ok((0, data)),
+    }
+}
+
+/// Visit every serialized `StarSystem` embedded in a document.
+fn for_each_system(kind: DocumentKind, data: &mut Value, mut visit: impl FnMut(&mut Value)) {
+    match kind {
+        DocumentKind::Snapshot => {
+            if let Some(systems) = data
+                .pointer_mut("/state/systems")
+                .and_then(Value::as_array_mut)
+            {
+                systems.iter_mut().for_each(&mut visit);
+            }
+            if let Some(in_transit) = data
+                .pointer_mut("/state/data_in_transit")
+                .and_then(Value::as_array_mut)
+            {
+                in_transit
+                    .iter_mut()
+                    .filter_map(|data| data.get_mut("system"))
+                    .for_each(visit);
+            }
+        }
+        DocumentKind::Event => {
+            // `SystemDiscovered` was renamed to `SystemScanned` in version 5,
+            // and that to `DiscoveryDataReceived` in version 11
+            for pointer in [
+                "/payload/SystemDiscovered/system",
+                "/payload/SystemScanned/system",
+                "/payload/DiscoveryDataTransmitted/system",
+                "/payload/DiscoveryDataReceived/system",
+            ] {
+                if let Some(system) = data.pointer_mut(pointer) {
+                    visit(system);
+                }
+            }
+            if let Some(systems) = data
+                .pointer_mut("/payload/GalaxyInitialized/systems")
+                .and_then(Value::as_array_mut)
+            {
+                systems.iter_mut().for_each(visit);
+            }
+        }
+    }
+}
+
+/// Every probe in flight in a snapshot, or the probe an event launches.
+fn launched_probes(
+    kind: DocumentKind,
+    data: &mut Value,
+) -> Result<Vec<&mut Map<String, Value>>, SchemaError> {
+    let probes: Vec<&mut Value> = match kind {
+        DocumentKind::Snapshot => snapshot_state(data)?
+            .get_mut("probes_in_flight")
+            .and_then(Value::as_array_mut)
+            .map(|probes| probes.iter_mut().collect())
+            .unwrap_or_default(),
+        DocumentKind::Event => data
+            .pointer_mut("/payload/ProbeLaunched")
+            .into_iter()
+            .collect(),
+    };
+    Ok(probes
+        .into_iter()
+        .filter_map(Value::as_object_mut)
+        .collect())
+}
+
+/// Rename an event payload variant, keeping its fields.
+fn rename_event(kind: DocumentKind, data: &mut Value, from: &str, to: &str) {
+    if kind == DocumentKind::Event
+        && let Some(payload) = data.get_mut("payload").and_then(Value::as_object_mut)
+        && let Some(fields) = payload.remove(from)
+    {
+        payload.insert(to.to_string(), fields);
+    }
+}
+
+fn snapshot_state(data: &mut Value) -> Result<&mut Map<String, Value>, SchemaError> {
+    data.get_mut("state")
+        .and_then(Value::as_object_mut)
+        .ok_or(SchemaError::Malformed("snapshot has no state"))
+}
+
+/// Version 1 added the game seed to `GameState`.
+fn v0_to_v1(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    if kind == DocumentKind::Snapshot {
+        let state = snapshot_state(data)?;
+        state.entry("seed").or_insert(Value::from(0));
+    }
+    Ok(())
+}
+
+/// Version 2 added the schedule to `GameState`; probes in flight become
+/// scheduled arrivals.
+fn v1_to_v2(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    if kind == DocumentKind::Snapshot {
+        let state = snapshot_state(data)?;
+        let probes = state
+            .get("probes_in_flight")
+            .and_then(Value::as_array)
+            .ok_or(SchemaError::Malformed("state has no probes_in_flight"))?;
+
+        let entries: Vec<Value> = probes
+            .iter()
+            .enumerate()
+            .map(|(id, probe)| {
+                json!({
+                    "id": id,
+                    "due": probe["arrival_time"],
+                    "action": { "type": "ProbeArrival", "probe_id": probe["id"] },
+                })
+            })
+            .collect();
+        state.insert(
+            "schedule".to_string(),
+            json!({ "next_id": entries.len(), "entries": entries }),
+        );
+    }
+    Ok(())
+}
+
+/// Version 3 gave star systems a position, distance from Sol and luminosity.
+/// Older systems are placed at the origin with solar luminosity.
+fn v2_to_v3(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    for_each_system(kind, data, |system| {
+        if let Some(system) = system.as_object_mut() {
+            system
+                .entry("position")
+                .or_insert(json!({ "x": 0.0, "y": 0.0, "z": 0.0 }));
+            system.entry("distance_from_sol").or_insert(json!(0.0));
+            system.entry("luminosity").or_insert(json!(1.0));
+        }
+    });
+    Ok(())
+}
+
+/// Version 4 recorded each probe's origin and travel distance. Older probes
+/// all flew the fixed 4.37 ly from Sol.
+fn v3_to_v4(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    for probe in launched_probes(kind, data)? {
+        probe.entry("origin_system_id").or_insert(Value::Null);
+        probe.entry("distance_ly").or_insert(json!(4.37));
+    }
+    Ok(())
+}
+
+/// Version 5 added discovery levels to systems and bodies and renamed
+/// `SystemDiscovered` to `SystemScanned`. Systems with bodies had been
+/// scanned by a probe; every body they list was visible.
+fn v4_to_v5(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    rename_event(kind, data, "SystemDiscovered", "SystemScanned");
+    for_each_system(kind, data, |system| {
+        let Some(system) = system.as_object_mut() else {
+            return;
+        };
+        let mut scanned = false;
+        if let Some(bodies) = system.get_mut("bodies").and_then(Value::as_array_mut) {
+            for body in bodies.iter_mut().filter_map(Value::as_object_mut) {
+                body.entry("discovery_level").or_insert(json!("Scanned"));
+                scanned = true;
+            }
+        }
+        let level = if scanned { "Scanned" } else { "Detected" };
+        system.entry("discovery_level").or_insert(json!(level));
+    });
+    Ok(())
+}
+
+/// Version 6 introduced probe classes; every earlier probe was a survey probe.
+fn v5_to_v6(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    for probe in launched_probes(kind, data)? {
+        probe.entry("probe_class").or_insert(json!("Survey"));
+    }
+    Ok(())
+}
+
+/// Version 7 recorded the ship-frame flight time of probes. Earlier travel
+/// ignored time dilation, so it equals the galaxy-frame flight time.
+fn v6_to_v7(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    let launched_at = data.get("game_time").and_then(Value::as_f64);
+    for probe in launched_probes(kind, data)? {
+        let departure = probe
+            .get("launched_at")
+            .and_then(Value::as_f64)
+            .or(launched_at)
+            .ok_or(SchemaError::Malformed("probe has no launch time"))?;
+        let arrival = match kind {
+            DocumentKind::Snapshot => probe.get("arrival_time"),
+            DocumentKind::Event => probe.get("eta"),
+        }
+        .and_then(Value::as_f64)
+        .ok_or(SchemaError::Malformed("probe has no arrival time"))?;
+        probe
+            .entry("ship_frame_hours")
+            .or_insert(json!(arrival - departure));
+    }
+    Ok(())
+}
+
+/// Version 8 tracked the condition of probes in flight; none were damaged.
+fn v7_to_v8(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    if kind == DocumentKind::Snapshot {
+        for probe in launched_probes(kind, data)? {
+            probe.entry("condition").or_insert(json!(1.0));
+        }
+    }
+    Ok(())
+}
+
+/// Version 9 tracked where and when a probe's current leg began. Until then
+/// every probe flew a single leg from its launch system.
+fn v8_to_v9(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    if kind != DocumentKind::Snapshot {
+        return Ok(());
+    }
+    let positions: Map<String, Value> = snapshot_state(data)?
+        .get("systems")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|system| {
+            Some((
+                system["id"].as_str()?.to_string(),
+                system["position"].clone(),
+            ))
+        })
+        .collect();
+    for probe in launched_probes(kind, data)? {
+        let departure = probe
+            .get("origin_system_id")
+            .and_then(Value::as_str)
+            .and_then(|origin| positions.get(origin).cloned())
+            .unwrap_or(json!({ "x": 0.0, "y": 0.0, "z": 0.0 }));
+        let launched_at = probe.get("launched_at").cloned().unwrap_or(json!(0.0));
+        probe.entry("departure").or_insert(departure);
+        probe.entry("departed_at").or_insert(launched_at);
+    }
+    Ok(())
+}
+
+/// Version 10 gave probes an itinerary of waypoints and tracked the distance
+/// they have flown. Earlier probes flew a single leg.
+fn v9_to_v10(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    for probe in launched_probes(kind, data)? {
+        probe.entry("waypoints").or_insert(json!([]));
+        if kind == DocumentKind::Snapshot {
+            probe.entry("distance_flown_ly").or_insert(json!(0.0));
+        }
+    }
+    Ok(())
+}
+
+/// Version 11 delayed scan results by the light-travel time back to Sol.
+/// Earlier scans were known the moment they were made, so `SystemScanned`
+/// becomes an immediate `DiscoveryDataReceived`.
+fn v10_to_v11(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    rename_event(kind, data, "SystemScanned", "DiscoveryDataReceived");
+    if kind == DocumentKind::Snapshot {
+        let state = snapshot_state(data)?;
+        state.entry("data_in_transit").or_insert(json!([]));
+    }
+    Ok(())
+}
+
+/// Version 12 gave bodies physical properties. Body types and spectral
+/// classes became enums, which serialize as the same names. Bodies generated
+/// earlier get typical values for their type: a Sun-like star, Earth-like
+/// planets at 1 AU and a main-belt-like rocky belt.
+fn v11_to_v12(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    for_each_system(kind, data, |system| {
+        let Some(bodies) = system.get_mut("bodies").and_then(Value::as_array_mut) else {
+            return;
+        };
+        for body in bodies.iter_mut().filter_map(Value::as_object_mut) {
+            let defaults = match body.get("body_type").and_then(Value::as_str) {
+                Some("Star") => json!({
+                    "mass": 332_946.0,
+                    "radius": 109.1,
+                    "orbit_au": 0.0,
+                    "composition": null,
+                    "surface_temperature": 5_772.0,
+                }),
+                Some("AsteroidBelt") => json!({
+                    "mass": 0.0005,
+                    "radius": 0.0,
+                    "orbit_au": 2.7,
+                    "composition": "Rocky",
+                    "surface_temperature": 170.0,
+                }),
+                _ => json!({
+                    "mass": 1.0,
+                    "radius": 1.0,
+                    "orbit_au": 1.0,
+                    "composition": "Rocky",
+                    "surface_temperature": 255.0,
+                }),
+            };
+            if let Value::Object(defaults) = defaults {
+                for (field, value) in defaults {
+                    body.entry(field).or_insert(value);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Version 13 gave star systems a mass and temperature. Systems generated
+/// earlier get the main-sequence values that match their luminosity.
+fn v12_to_v13(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    for_each_system(kind, data, |system| {
+        let Some(system) = system.as_object_mut() else {
+            return;
+        };
+        let luminosity = system
+            .get("luminosity")
+            .and_then(Value::as_f64)
+            .unwrap_or(1.0);
+        let mass = luminosity.powf(0.25);
+        let radius = if mass < 1.0 {
+            mass.powf(0.8)
+        } else {
+            mass.powf(0.57)
+        };
+        let temperature = 5_772.0 * (luminosity / (radius * radius)).powf(0.25);
+        system.entry("mass").or_insert(json!(mass));
+        system.entry("temperature").or_insert(json!(temperature));
+    });
+    Ok(())
+}
+
+/// Version 14 arranged bodies in a tree with a parent reference. Systems
+/// generated earlier have no moons, so every body orbits the star.
+fn v13_to_v14(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    for_each_system(kind, data, |system| {
+        let Some(bodies) = system.get_mut("bodies").and_then(Value::as_array_mut) else {
+            return;
+        };
+        let star_id = bodies
+            .iter()
+            .find(|body| body.get("body_type").and_then(Value::as_str) == Some("Star"))
+            .and_then(|star| star.get("id"))
+            .cloned()
+            .unwrap_or(Value::Null);
+        for body in bodies.iter_mut().filter_map(Value::as_object_mut) {
+            let parent_id = if body.get("body_type").and_then(Value::as_str) == Some("Star") {
+                Value::Null
+            } else {
+                star_id.clone()
+            };
+            body.entry("parent_id").or_insert(parent_id);
+        }
+    });
+    Ok(())
+}
+
+/// Version 15 moved asteroid belts out of the body list into annular regions,
+/// and gave systems an Oort cloud. An old belt body becomes a belt of typical
+/// density spanning 20% either side of its orbit; generated systems get an
+/// Oort cloud sized for their star.
+fn v14_to_v15(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    for_each_system(kind, data, |system| {
+        let Some(system) = system.as_object_mut() else {
+            return;
+        };
+        let mut belts = Vec::new();
+        if let Some(bodies) = system.get_mut("bodies").and_then(Value::as_array_mut) {
+            bodies.retain(|body| {
+                if body.get("body_type").and_then(Value::as_str) != Some("AsteroidBelt") {
+                    return true;
+                }
+                let orbit_au = body.get("orbit_au").and_then(Value::as_f64).unwrap_or(2.7);
+                let composition = body
+                    .get("composition")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Rocky");
+                belts.push(json!({
+                    "id": body.get("id"),
+                    "name": body.get("name"),
+                    "discovery_level": body.get("discovery_level"),
+                    "inner_radius_au": orbit_au * 0.8,
+                    "outer_radius_au": orbit_au * 1.2,
+                    "density": 1.0,
+                    "composition": composition,
+                }));
+                false
+            });
+        }
+        let generated = system
+            .get("bodies")
+            .and_then(Value::as_array)
+            .is_some_and(|bodies| !bodies.is_empty());
+        let mass = system.get("mass").and_then(Value::as_f64).unwrap_or(1.0);
+
+        system.entry("belts").or_insert(Value::Array(belts));
+        let oort_cloud = if generated {
+            json!({ "radius_au": 100_000.0 * mass.cbrt() })
+        } else {
+            Value::Null
+        };
+        system.entry("oort_cloud").or_insert(oort_cloud);
+    });
+    Ok(())
+}
+
+/// Version 16 allowed several stars per system, so belts record which star
+/// they circle. Systems generated earlier have a single star.
+fn v15_to_v16(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    for_each_system(kind, data, |system| {
+        let star_id = system
+            .get("bodies")
+            .and_then(Value::as_array)
+            .and_then(|bodies| {
+                bodies
+                    .iter()
+                    .find(|body| body.get("parent_id").is_some_and(Value::is_null))
+            })
+            .and_then(|star| star.get("id"))
+            .cloned()
+            .unwrap_or(Value::Null);
+        let Some(belts) = system.get_mut("belts").and_then(Value::as_array_mut) else {
+            return;
+        };
+        for belt in belts.iter_mut().filter_map(Value::as_object_mut) {
+            belt.entry("parent_id").or_insert(star_id.clone());
+        }
+    });
+    Ok(())
+}
+
+/// Version 17 recorded which system is Sol in `GameState`, where it used to be
+/// looked up as the first system at the origin.
+fn v16_to_v17(kind: DocumentKind, data: &mut Value) -> Result<(), SchemaError> {
+    if kind != DocumentKind::Snapshot {
+        return Ok(());
+    }
+    let state = snapshot_state(data)?;
+    let origin = json!({ "x": 0.0, "y": 0.0, "z": 0.0 });
+    let sol_id = state
+        .get("systems")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .find(|system| system.get("position") == Some(&origin))
+        .and_then(|sol| sol.get("id"))
+        .cloned()
+        .unwrap_or(Value::Null);
+    state.entry("sol_id").or_insert(sol_id);
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::schema::{self, DocumentKind};
+use super::{EventStoreError, FileEventStore, crc32};
+use crate::GameState;
+use crate::systems::apply;
+
+const SNAPSHOT_PREFIX: &str = "snapshot-";
+const SNAPSHOT_EXTENSION: &str = "json";
+
+#[derive(Debug, Error)]
+pub enum SnapshotStoreError {
+    #[error("snapshot store I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to serialize snapshot: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error(transparent)]
+    Events(#[from] EventStoreError),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SnapshotConfig {
+    /// Take a snapshot once this many events have been appended since the last one
+    pub every_n_events: u64,
+    /// Number of most recent snapshots kept on disk
+    pub retain: usize,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            every_n_events: 1000,
+            retain: 3,
+        }
+    }
+}
+
+/// A `GameState` that reflects every event with an offset below `next_offset`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub next_offset: u64,
+    pub state: GameState,
+}
+
+/// Directory of periodic `GameState` snapshots, one file per snapshot.
+///
+/// Files hold a single `next_offset|crc32|json` record with a schema-versioned
+/// document, mirroring the event log format, and are written to a temporary
+/// file first so a crash never leaves a half-written snapshot under its final
+/// name.
+pub struct SnapshotStore {
+    dir: PathBuf,
+    config: SnapshotConfig,
+    last_offset: Option<u64>,
+}
+
+impl SnapshotStore {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, SnapshotStoreError> {
+        Self::open_with_config(dir, SnapshotConfig::default())
+    }
+
+    pub fn open_with_config(
+        dir: impl AsRef<Path>,
+        config: SnapshotConfig,
+    ) -> Result<Self, SnapshotStoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let last_offset = list_snapshots(&dir)?.last().map(|(offset, _)| *offset);
+        Ok(Self {
+            dir,
+            config,
+            last_offset,
+        })
+    }
+
+    /// Write a snapshot and prune old ones according to the retention policy.
+    pub fn save(&mut self, next_offset: u64, state: &GameState) -> Result<(), SnapshotStoreError> {
+        let json = schema::encode(&Snapshot {
+            next_offset,
+            state: state.clone(),
+        })?;
+        let record = format!("{}|{:08x}|{}", next_offset, crc32(json.as_bytes()), json);
+
+        let path = snapshot_path(&self.dir, next_offset);
+        let tmp_path = path.with_extension("tmp");
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(record.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &path)?;
+
+        self.last_offset = Some(next_offset);
+        self.prune()
+    }
+
+    /// Save only if enough events have accumulated since the last snapshot.
+    /// Returns whether a snapshot was taken.
+    pub fn maybe_save(
+        &mut self,
+        next_offset: u64,
+        state: &GameState,
+    ) -> Result<bool, SnapshotStoreError> {
+        let since_last = next_offset.saturating_sub(self.last_offset.unwrap_or(0));
+        if since_last < self.config.every_n_events {
+            return Ok(false);
+        }
+        self.save(next_offset, state)?;
+        Ok(true)
+    }
+
+    /// Newest snapshot that parses, passes its checksum and does not claim
+    /// more events than `max_offset`. Invalid snapshots are skipped.
+    pub fn latest_valid(&self, max_offset: u64) -> Result<Option<Snapshot>, SnapshotStoreError> {
+        for (offset, path) in list_snapshots(&self.dir)?.into_iter().rev() {
+            if offset > max_offset {
+                continue;
+            }
+            if let Some(snapshot) = read_snapshot(&path, offset) {
+                return Ok(Some(snapshot));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Rebuild the current state: start from the newest valid snapshot (or
+    /// `initial` if there is none) and replay only the tail of the event log.
+    pub fn restore(
+        &self,
+        events: &FileEventStore,
+        initial: GameState,
+    ) -> Result<GameState, SnapshotStoreError> {
+        let (mut state, from) = match self.latest_valid(events.next_offset())? {
+            Some(snapshot) => (snapshot.state, snapshot.next_offset),
+            None => (initial, 0),
+        };
+
+        for event in events.read_from(from)? {
+            state = apply(state, &event?.payload);
+        }
+        Ok(state)
+    }
+
+    /// Delete all but the `retain` most recent snapshots.
+    pub fn prune(&self) -> Result<(), SnapshotStoreError> {
+        let snapshots = list_snapshots(&self.dir)?;
+        let excess = snapshots.len().saturating_sub(self.config.retain.max(1));
+        for (_, path) in snapshots.into_iter().take(excess) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+fn snapshot_path(dir: &Path, next_offset: u64) -> PathBuf {
+    dir.join(format!(
+        "{SNAPSHOT_PREFIX}{next_offset:020}.{SNAPSHOT_EXTENSION}"
+    ))
+}
+
+/// All snapshot files in `dir`, oldest first.
+fn list_snapshots(dir: &Path) -> Result<Vec<(u64, PathBuf)>, SnapshotStoreError> {
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let offset = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(SNAPSHOT_PREFIX))
+            .and_then(|name| name.strip_suffix(&format!(".{SNAPSHOT_EXTENSION}")))
+            .and_then(|offset| offset.parse::<u64>().ok());
+        if let Some(offset) = offset {
+            snapshots.push((offset, path));
+        }
+    }
+    snapshots.sort_by_key(|(offset, _)| *offset);
+    Ok(snapshots)
+}
+
+fn read_snapshot(path: &Path, expected_offset: u64) -> Option<Snapshot> {
+    let record = fs::read_to_string(path).ok()?;
+    let mut parts = record.splitn(3, '|');
+    let offset: u64 = parts.next()?.parse().ok()?;
+    let checksum = u32::from_str_radix(parts.next()?, 16).ok()?;
+    let json = parts.next()?;
+
+    if offset != expected_offset || crc32(json.as_bytes()) != checksum {
+        return None;
+    }
+    let snapshot: Snapshot = schema::decode(DocumentKind::Snapshot, json).ok()?;
+    (snapshot.next_offset == offset).then_some(snapshot)
+}
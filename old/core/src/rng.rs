@@ -0,0 +1,43 @@
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+/// Deterministic random stream used by every generator in the reducer.
+///
+/// The stream is derived from the game seed and the event offset of the
+/// command being reduced, so replaying the same commands at the same offsets
+/// always produces the same values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRng {
+    inner: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn from_seed_and_offset(seed: u64, offset: u64) -> Self {
+        let mut inner = ChaCha8Rng::seed_from_u64(seed);
+        inner.set_stream(offset);
+        Self { inner }
+    }
+
+    /// Mint a ULID whose timestamp part is the game time (in hours) rather
+    /// than the wall clock.
+    pub fn next_ulid(&mut self, game_time: f64) -> Ulid {
+        let timestamp_ms = (game_time.max(0.0) * 3_600_000.0) as u64;
+        Ulid::from_parts(timestamp_ms, self.inner.random())
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.inner.fill_bytes(dst)
+    }
+}
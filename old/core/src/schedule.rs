@@ -0,0 +1,162 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ProbeId, StarSystemId};
+
+/// Something that should happen at a given game time. `AdvanceTime` fires
+/// these in time order; each one is removed by the event that handles it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ScheduledAction {
+    ProbeArrival { probe_id: ProbeId },
+    SystemExploration { system_id: StarSystemId },
+    DiscoveryDataArrival { system_id: StarSystemId },
+}
+
+/// Position in the schedule: due time first, then the order actions were
+/// scheduled in, so simultaneous actions fire first-come first-served.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScheduleKey {
+    pub due: f64,
+    pub id: u64,
+}
+
+impl Eq for ScheduleKey {}
+
+impl Ord for ScheduleKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.due
+            .total_cmp(&other.due)
+            .then_with(|| self.id.cmp(&other.id))
+    }
+}
+
+impl PartialOrd for ScheduleKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledEntry {
+    pub id: u64,
+    pub due: f64,
+    pub action: ScheduledAction,
+}
+
+impl ScheduledEntry {
+    pub fn key(&self) -> ScheduleKey {
+        ScheduleKey {
+            due: self.due,
+            id: self.id,
+        }
+    }
+}
+
+/// Time-ordered queue of pending actions. Serialized as a list sorted by due
+/// time so saved documents are stable.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "ScheduleDocument", into = "ScheduleDocument")]
+pub struct Schedule {
+    next_id: u64,
+    entries: BTreeMap<ScheduleKey, ScheduledAction>,
+    /// Where each action sits in `entries`, for removal without a scan
+    index: HashMap<ScheduledAction, ScheduleKey>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ScheduleDocument {
+    next_id: u64,
+    entries: Vec<ScheduledEntry>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, due: f64, action: ScheduledAction) -> ScheduleKey {
+        let key = ScheduleKey {
+            due,
+            id: self.next_id,
+        };
+        self.next_id += 1;
+        self.remove(&action);
+        self.index.insert(action.clone(), key);
+        self.entries.insert(key, action);
+        key
+    }
+
+    /// Earliest entry ordered after `after` (or the first entry if `None`)
+    /// that is due no later than `until`.
+    pub fn next_due(&self, after: Option<ScheduleKey>, until: f64) -> Option<ScheduledEntry> {
+        let lower = after.map_or(Bound::Unbounded, Bound::Excluded);
+        self.entries
+            .range((lower, Bound::Unbounded))
+            .next()
+            .filter(|(key, _)| key.due <= until)
+            .map(|(key, action)| ScheduledEntry {
+                id: key.id,
+                due: key.due,
+                action: action.clone(),
+            })
+    }
+
+    /// Remove a pending action. Each action is scheduled at most once.
+    pub fn remove(&mut self, action: &ScheduledAction) {
+        if let Some(key) = self.index.remove(action) {
+            self.entries.remove(&key);
+        }
+    }
+
+    pub fn contains(&self, action: &ScheduledAction) -> bool {
+        self.index.contains_key(action)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ScheduledEntry> + '_ {
+        self.entries.iter().map(|(key, action)| ScheduledEntry {
+            id: key.id,
+            due: key.due,
+            action: action.clone(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl From<ScheduleDocument> for Schedule {
+    fn from(document: ScheduleDocument) -> Self {
+        let entries: BTreeMap<_, _> = document
+            .entries
+            .into_iter()
+            .map(|entry| (entry.key(), entry.action))
+            .collect();
+        let index = entries
+            .iter()
+            .map(|(key, action)| (action.clone(), *key))
+            .collect();
+        Self {
+            next_id: document.next_id,
+            entries,
+            index,
+        }
+    }
+}
+
+impl From<Schedule> for ScheduleDocument {
+    fn from(schedule: Schedule) -> Self {
+        Self {
+            next_id: schedule.next_id,
+            entries: schedule.iter().collect(),
+        }
+    }
+}
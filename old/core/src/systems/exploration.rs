@@ -1,63 +1,488 @@
-use strum::IntoEnumIterator;
-
+use crate::catalog::*;
 use crate::domain::*;
-use rand::{prelude::*, seq::SliceRandom};
-use strum::{Display as StrumDisplay, EnumIter};
-
-// pub const SPECTRAL_CLASSES: Vec<&'static str> = vec!["O", "B", "A", "F", "G", "K", "M"];
-
-#[derive(EnumIter, Debug, Clone, Copy, StrumDisplay, PartialEq, Eq, Hash)]
-pub enum SpectralClass {
-    O,
-    B,
-    A,
-    F,
-    G,
-    K,
-    M,
-}
-
-#[derive(EnumIter, Debug, Clone, Copy, StrumDisplay, PartialEq, Eq, Hash)]
-pub enum CelestialBodyType {
-    Star,
-    Planet,
-    AsteroidBelt,
-}
-
-pub fn calculate_travel_time(distance_ly: f32) -> f64 {
-    // simple modeL: 1 ly = 100 game hours
-    (distance_ly as f64) * 100.0
-}
-
-// Procedurally generate a star system
-// Note: this algorithm assumes a single star in the center of the system.
-pub fn generate_system(system_id: StarSystemId) -> StarSystem {
-    let mut rng = rand::rng();
-
-    let spectral_classes: Vec<SpectralClass> = SpectralClass::iter().collect();
-    let mut body_types: Vec<CelestialBodyType> = CelestialBodyType::iter().collect();
-    body_types.retain(|&t| t != CelestialBodyType::Star);
-    let spectral_class = spectral_classes.choose(&mut rng).unwrap().to_string();
-
-    let num_bodies = rng.random_range(1..=8); // 1 to 8 bodies
-    let bodies = (0..num_bodies)
-        .map(|i| CelestialBody {
-            id: CelestialBodyId::new(),
-            name: format!("Body-{}", i + 1),
-            body_type: if i == 0 {
-                CelestialBodyType::Star.to_string()
-            } else {
-                body_types.choose(&mut rng).unwrap().to_string()
-            },
-        })
-        .collect::<Vec<_>>();
-
-    // 1 to 8 bodies
-    let name = format!("System-{}", system_id.0.to_string());
+use crate::rng::GameRng;
+use crate::systems::galaxy::main_sequence;
+use crate::systems::naming::*;
+use crate::travel::*;
+use rand::prelude::*;
+use ulid::Ulid;
+
+/// Earth masses in one solar mass
+const EARTH_MASSES_PER_SUN: f64 = 332_946.0;
+/// Earth radii in one solar radius
+const EARTH_RADII_PER_SUN: f64 = 109.1;
+/// Earth's radius in AU
+const EARTH_RADIUS_AU: f64 = 4.2635e-5;
+/// Temperature of the Sun's photosphere in kelvin
+pub const SUN_TEMPERATURE: f64 = 5_772.0;
+
+/// Outer edge of Sol's Oort cloud in AU, where the galactic tide strips away
+/// loosely bound bodies. It grows with the cube root of the stars' mass.
+pub const OORT_CLOUD_RADIUS_AU: f64 = 100_000.0;
+
+/// Outer edge of the protoplanetary disk, in multiples of the frost line
+/// distance; Neptune orbits at about 11.
+const DISK_EDGE_FROST_LINES: f64 = 30.0;
+
+/// Time a full survey of a scanned system takes
+pub const EXPLORATION_DURATION_HOURS: f64 = 720.0;
+
+pub fn calculate_travel_time(distance_ly: f64, probe_class: ProbeClass) -> TravelTime {
+    travel_time(distance_ly, &probe_class.spec().drive)
+}
+
+// Light-travel time for a signal sent from `position` to reach Sol
+pub fn signal_delay(position: &Position) -> f64 {
+    position.distance_to(&Position::ORIGIN) * HOURS_PER_YEAR
+}
+
+// Where a probe on its current leg towards `target` is at `game_time`
+pub fn probe_position(probe: &ProbeInFlight, target: &Position, game_time: f64) -> Position {
+    if probe.distance_ly <= 0.0 {
+        return *target;
+    }
+    let covered = distance_covered(
+        probe.distance_ly,
+        &probe.probe_class.spec().drive,
+        game_time - probe.departed_at,
+    );
+    probe.departure.lerp(target, covered / probe.distance_ly)
+}
+
+// Procedurally generate the bodies of a detected star system, as seen by a
+// probe scan: only bodies the probe's sensors can resolve are revealed, the
+// rest stay unknown until the system is explored.
+// The detected star may have companions. Planets then only form where their
+// orbits stay stable (see `planet_hosts`): around each star, and around
+// close pairs from well outside the pair.
+// Orbits are spaced geometrically outwards from their host, as in the Solar
+// System, and what forms at each one depends on where it lies against the
+// host's frost line: rocky worlds inside it, gas giants just beyond it where
+// ice was most plentiful, and ice giants and icy worlds further out. Each
+// planet then gets its moons, listed right after it, and belts fill the gaps
+// left between the planets.
+// Bodies are named after their host as astronomers do: companion stars get
+// capital letters, planets lowercase letters from b outwards, and moons Roman
+// numerals. A name someone already chose for another body is numbered, see
+// `unique_body_name`; the detected star keeps its system's name.
+// All randomness (including body ids) comes from `rng`, so the same stream
+// always yields the same system.
+pub fn generate_system(
+    detected: &StarSystem,
+    sensor_resolution: f64,
+    is_taken: impl Fn(&str) -> bool,
+    rng: &mut GameRng,
+) -> StarSystem {
+    let max_planets = rng.random_range(0..8);
+    let mut bodies = generate_stars(detected, &is_taken, rng);
+    let total_mass: f64 = bodies.iter().map(|star| star.mass).sum::<f64>() / EARTH_MASSES_PER_SUN;
+
+    let mut belts = Vec::new();
+    for host in planet_hosts(detected, &bodies) {
+        let first = bodies.len();
+        generate_planets(detected, &host, max_planets, &mut bodies, &is_taken, rng);
+        let new_belts = generate_belts(detected, &host, &bodies[first..], &is_taken, rng);
+        belts.extend(new_belts);
+    }
+    let oort_cloud = OortCloud {
+        radius_au: OORT_CLOUD_RADIUS_AU * total_mass.cbrt() * rng.random_range(0.8..1.2),
+    };
+
+    for body in &mut bodies {
+        if sensor_resolution >= detection_threshold(body.body_type) {
+            body.discovery_level = DiscoveryLevel::Scanned;
+        }
+    }
+    if sensor_resolution >= BELT_DETECTION_THRESHOLD {
+        for belt in &mut belts {
+            belt.discovery_level = DiscoveryLevel::Scanned;
+        }
+    }
+
     StarSystem {
-        id: system_id,
-        name,
-        spectral_class,
+        discovery_level: DiscoveryLevel::Scanned,
         bodies,
+        belts,
+        oort_cloud: Some(oort_cloud),
+        ..detected.clone()
+    }
+}
+
+fn body_id(system: &StarSystem, rng: &mut GameRng) -> CelestialBodyId {
+    CelestialBodyId(Ulid::from_parts(system.id.0.timestamp_ms(), rng.random()))
+}
+
+// The detected star followed by its companions. Multiple systems are built
+// hierarchically: each companion orbits everything before it, either close in
+// or far out, and a third star only joins a close pair from at least ten
+// times further out, where the triple stays stable. Heavier stars are more
+// often multiple.
+fn generate_stars(
+    system: &StarSystem,
+    is_taken: &impl Fn(&str) -> bool,
+    rng: &mut GameRng,
+) -> Vec<CelestialBody> {
+    let multiple_chance = match system.spectral_class {
+        SpectralClass::O | SpectralClass::B => 0.7,
+        SpectralClass::A | SpectralClass::F => 0.5,
+        SpectralClass::G => 0.45,
+        SpectralClass::K => 0.4,
+        SpectralClass::M => 0.25,
+    };
+    let num_companions = match rng.random::<f64>() {
+        r if r < multiple_chance * 0.2 => 2,
+        r if r < multiple_chance => 1,
+        _ => 0,
+    };
+
+    let mut primary = star_body(system, rng);
+    if num_companions > 0 {
+        primary.name = unique_body_name(format!("{} {}", system.name, star_letter(0)), is_taken);
+    }
+    let primary_id = primary.id;
+    let mut stars = vec![primary];
+    let mut separation_au = 0.0;
+    for i in 0..num_companions {
+        separation_au = if i > 0 {
+            separation_au * 10f64.powf(rng.random_range(1.0..2.5))
+        } else if num_companions > 1 || rng.random_bool(0.5) {
+            10f64.powf(rng.random_range(-1.3..0.7)) // close: 0.05 to 5 AU
+        } else {
+            10f64.powf(rng.random_range(1.7..3.7)) // wide: 50 to 5000 AU
+        };
+        let mass = (system.mass * rng.random_range(0.1..1.0)).max(0.08);
+        let (luminosity, temperature) = main_sequence(mass);
+
+        stars.push(CelestialBody {
+            id: body_id(system, rng),
+            name: unique_body_name(
+                format!("{} {}", system.name, star_letter(stars.len())),
+                is_taken,
+            ),
+            body_type: CelestialBodyType::Star,
+            parent_id: Some(primary_id),
+            discovery_level: DiscoveryLevel::Unknown,
+            mass: mass * EARTH_MASSES_PER_SUN,
+            radius: star_radius(luminosity, temperature),
+            orbit_au: separation_au,
+            composition: None,
+            surface_temperature: temperature,
+        });
+    }
+    stars
+}
+
+// The detected star itself
+fn star_body(system: &StarSystem, rng: &mut GameRng) -> CelestialBody {
+    CelestialBody {
+        id: body_id(system, rng),
+        name: system.name.clone(),
+        body_type: CelestialBodyType::Star,
+        parent_id: None,
+        discovery_level: DiscoveryLevel::Unknown,
+        mass: system.mass * EARTH_MASSES_PER_SUN,
+        radius: star_radius(system.luminosity, system.temperature),
+        orbit_au: 0.0,
+        composition: None,
+        surface_temperature: system.temperature,
+    }
+}
+
+// Radius in Earth radii from the Stefan-Boltzmann law
+fn star_radius(luminosity: f64, temperature: f64) -> f64 {
+    luminosity.sqrt() * (SUN_TEMPERATURE / temperature).powi(2) * EARTH_RADII_PER_SUN
+}
+
+// Luminosity relative to Sol, inverting `star_radius`
+fn star_luminosity(star: &CelestialBody) -> f64 {
+    (star.radius / EARTH_RADII_PER_SUN).powi(2)
+        * (star.surface_temperature / SUN_TEMPERATURE).powi(4)
+}
+
+// A region where planets can orbit stably: around a single star (S-type) or
+// around a whole group of stars (P-type), whose light then adds up
+struct PlanetHost {
+    parent_id: CelestialBodyId,
+    /// The star's name, or the system's followed by the group's letters
+    name: String,
+    luminosity: f64,
+    inner_au: f64,
+    outer_au: f64,
+}
+
+impl PlanetHost {
+    // Where the stable region or the protoplanetary disk ends, whichever is
+    // closer in
+    fn disk_edge_au(&self) -> f64 {
+        let disk_au = DISK_EDGE_FROST_LINES * frost_line_au(self.luminosity);
+        self.outer_au.min(disk_au)
     }
 }
+
+// Stable planet regions of a hierarchical multiple, following Holman &
+// Wiegert (1999) for circular orbits. Around one star of a pair, planets are
+// stable out to a fraction of the separation that shrinks as the companion's
+// share `mu` of the pair's mass grows; around the pair, from a few times
+// the separation. Each region is also bounded by the next companion out.
+fn planet_hosts(system: &StarSystem, stars: &[CelestialBody]) -> Vec<PlanetHost> {
+    let s_type_limit = |mu: f64| 0.464 - 0.380 * mu;
+    let p_type_limit = |mu: f64| 1.60 + 4.12 * mu - 5.09 * mu * mu;
+
+    let mut hosts = Vec::new();
+    let mut group_mass = 0.0;
+    let mut group_luminosity = 0.0;
+    for (i, star) in stars.iter().enumerate() {
+        let luminosity = star_luminosity(star);
+        let bound_au = stars.get(i + 1).map_or(f64::INFINITY, |next| {
+            let mu = next.mass / (group_mass + star.mass + next.mass);
+            s_type_limit(mu) * next.orbit_au
+        });
+
+        let outer_au = if i == 0 {
+            bound_au
+        } else {
+            s_type_limit(group_mass / (group_mass + star.mass)) * star.orbit_au
+        };
+        hosts.push(PlanetHost {
+            parent_id: star.id,
+            name: star.name.clone(),
+            luminosity,
+            inner_au: 0.0,
+            outer_au,
+        });
+
+        group_mass += star.mass;
+        group_luminosity += luminosity;
+        if i > 0 {
+            let letters: String = (0..=i).map(star_letter).collect();
+            hosts.push(PlanetHost {
+                parent_id: stars[0].id,
+                name: format!("{} {letters}", system.name),
+                luminosity: group_luminosity,
+                inner_au: p_type_limit(star.mass / group_mass) * star.orbit_au,
+                outer_au: bound_au,
+            });
+        }
+    }
+    hosts
+}
+
+// Place up to `max_planets` planets in a host's stable region, each followed
+// by its moons
+fn generate_planets(
+    system: &StarSystem,
+    host: &PlanetHost,
+    max_planets: usize,
+    bodies: &mut Vec<CelestialBody>,
+    is_taken: &impl Fn(&str) -> bool,
+    rng: &mut GameRng,
+) {
+    let frost_line = frost_line_au(host.luminosity);
+    let outer_au = host.disk_edge_au();
+    let mut orbit_au = rng.random_range(0.2..0.5) * host.luminosity.sqrt();
+    if orbit_au < host.inner_au {
+        orbit_au = host.inner_au * rng.random_range(1.0..1.3);
+    }
+
+    for i in 0..max_planets {
+        if i > 0 {
+            orbit_au *= rng.random_range(1.4..2.2);
+        }
+        if orbit_au > outer_au {
+            break;
+        }
+        let composition = sample_planet_composition(orbit_au / frost_line, rng);
+        let (mass, radius) = sample_size(composition, rng);
+        let planet = CelestialBody {
+            id: body_id(system, rng),
+            name: unique_body_name(format!("{} {}", host.name, planet_letter(i)), is_taken),
+            body_type: CelestialBodyType::Planet,
+            parent_id: Some(host.parent_id),
+            discovery_level: DiscoveryLevel::Unknown,
+            mass,
+            radius,
+            orbit_au,
+            composition: Some(composition),
+            surface_temperature: equilibrium_temperature(host.luminosity, orbit_au),
+        };
+
+        let moons = generate_moons(system, &planet, frost_line, is_taken, rng);
+        bodies.push(planet);
+        bodies.extend(moons);
+    }
+}
+
+// Giant planets keep families of moons, rocky and icy worlds only now and
+// then capture or knock one loose. Moons are far smaller than their parent,
+// and orbit a few to a few dozen parent radii out.
+fn generate_moons(
+    system: &StarSystem,
+    planet: &CelestialBody,
+    frost_line: f64,
+    is_taken: &impl Fn(&str) -> bool,
+    rng: &mut GameRng,
+) -> Vec<CelestialBody> {
+    let (num_moons, mass_share) = match planet.composition {
+        Some(Composition::GasGiant) => (rng.random_range(2..=8), 1e-5..2e-4),
+        Some(Composition::IceGiant) => (rng.random_range(1..=5), 1e-5..1e-4),
+        _ if rng.random_bool(0.3) => (rng.random_range(1..=2), 1e-3..2e-2),
+        _ => (0, 1e-3..2e-2),
+    };
+    let composition = if planet.orbit_au < frost_line {
+        Composition::Rocky
+    } else {
+        Composition::Icy
+    };
+
+    let mut orbit_radii = rng.random_range(3.0..10.0);
+    let mut moons = Vec::with_capacity(num_moons);
+    for i in 0..num_moons {
+        if i > 0 {
+            orbit_radii *= rng.random_range(1.3..2.0);
+        }
+        let mass = planet.mass * rng.random_range(mass_share.clone());
+        moons.push(CelestialBody {
+            id: body_id(system, rng),
+            name: unique_body_name(
+                format!("{} {}", planet.name, roman_numeral(i + 1)),
+                is_taken,
+            ),
+            body_type: CelestialBodyType::Moon,
+            parent_id: Some(planet.id),
+            discovery_level: DiscoveryLevel::Unknown,
+            mass,
+            radius: solid_radius(composition, mass),
+            orbit_au: orbit_radii * planet.radius * EARTH_RADIUS_AU,
+            composition: Some(composition),
+            surface_temperature: planet.surface_temperature,
+        });
+    }
+    moons
+}
+
+// Belts form in wide gaps between planets, where a neighbour's gravity kept
+// the material from gathering into a planet: most often just inside the
+// innermost giant, as the main belt lies inside Jupiter. Leftover material
+// past the outermost planet, or around the frost line of a host without
+// planets, may form a belt like the Kuiper belt.
+fn generate_belts(
+    system: &StarSystem,
+    host: &PlanetHost,
+    bodies: &[CelestialBody],
+    is_taken: &impl Fn(&str) -> bool,
+    rng: &mut GameRng,
+) -> Vec<AsteroidBelt> {
+    let frost_line = frost_line_au(host.luminosity);
+    let planets: Vec<&CelestialBody> = bodies
+        .iter()
+        .filter(|body| body.body_type == CelestialBodyType::Planet)
+        .collect();
+    let first_giant = planets.iter().position(|planet| {
+        matches!(
+            planet.composition,
+            Some(Composition::GasGiant | Composition::IceGiant)
+        )
+    });
+
+    let mut belts = Vec::new();
+    let mut add_belt = |inner_radius_au: f64, outer_radius_au: f64, rng: &mut GameRng| {
+        let composition = if (inner_radius_au + outer_radius_au) / 2.0 < frost_line {
+            Composition::Rocky
+        } else {
+            Composition::Icy
+        };
+        belts.push(AsteroidBelt {
+            id: body_id(system, rng),
+            name: unique_body_name(
+                format!("{} Belt {}", host.name, roman_numeral(belts.len() + 1)),
+                is_taken,
+            ),
+            parent_id: host.parent_id,
+            discovery_level: DiscoveryLevel::Unknown,
+            inner_radius_au,
+            outer_radius_au,
+            density: 10f64.powf(rng.random_range(-1.0..1.0)),
+            composition,
+        });
+    };
+
+    for (i, pair) in planets.windows(2).enumerate() {
+        let (inner, outer) = (pair[0].orbit_au, pair[1].orbit_au);
+        let chance = if first_giant == Some(i + 1) { 0.7 } else { 0.2 };
+        if outer / inner >= 1.8 && rng.random_bool(chance) {
+            add_belt(inner * 1.2, outer * 0.7, rng);
+        }
+    }
+
+    if rng.random_bool(0.5) {
+        let inner = planets
+            .last()
+            .map_or(frost_line.max(host.inner_au), |planet| {
+                planet.orbit_au * 1.5
+            });
+        let outer = (inner * rng.random_range(1.5..2.5)).min(host.disk_edge_au());
+        if inner < outer {
+            add_belt(inner, outer, rng);
+        }
+    }
+    belts
+}
+
+// What forms at an orbit `frost_ratio` times the frost line distance
+fn sample_planet_composition(frost_ratio: f64, rng: &mut GameRng) -> Composition {
+    let roll = rng.random::<f64>();
+    match frost_ratio {
+        f if f < 1.0 => Composition::Rocky,
+        f if f < 3.0 => match roll {
+            r if r < 0.65 => Composition::GasGiant,
+            _ => Composition::IceGiant,
+        },
+        _ => match roll {
+            r if r < 0.5 => Composition::IceGiant,
+            _ => Composition::Icy,
+        },
+    }
+}
+
+// Mass in Earth masses and radius in Earth radii
+fn sample_size(composition: Composition, rng: &mut GameRng) -> (f64, f64) {
+    match composition {
+        Composition::Rocky => {
+            let mass = rng.random_range(0.05..5.0);
+            (mass, solid_radius(composition, mass))
+        }
+        Composition::Icy => {
+            let mass = rng.random_range(0.01..1.0);
+            (mass, solid_radius(composition, mass))
+        }
+        Composition::IceGiant => (rng.random_range(10.0..25.0), rng.random_range(3.5..4.5)),
+        Composition::GasGiant => (rng.random_range(50.0..1000.0), rng.random_range(9.0..12.0)),
+    }
+}
+
+// Radius in Earth radii of a rocky or icy world of `mass` Earth masses; ice is
+// less dense than rock, so an icy world is larger for its mass
+fn solid_radius(composition: Composition, mass: f64) -> f64 {
+    match composition {
+        Composition::Icy => 1.2 * mass.powf(0.3),
+        _ => mass.powf(0.27),
+    }
+}
+
+// Blackbody equilibrium temperature at `orbit_au` from a star of the given
+// luminosity relative to Sol
+fn equilibrium_temperature(luminosity: f64, orbit_au: f64) -> f64 {
+    278.6 * luminosity.powf(0.25) / orbit_au.sqrt()
+}
+
+// Sensor resolution needed to pick a body out during a scan
+fn detection_threshold(body_type: CelestialBodyType) -> f64 {
+    match body_type {
+        CelestialBodyType::Star => 0.0,
+        CelestialBodyType::Planet => 0.5,
+        CelestialBodyType::Moon => 0.7,
+    }
+}
+
+// Sensor resolution needed to pick out an asteroid belt
+const BELT_DETECTION_THRESHOLD: f64 = 0.8;
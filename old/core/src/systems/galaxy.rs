@@ -0,0 +1,134 @@
+use std::collections::HashSet;
+use std::f64::consts::TAU;
+
+use rand::prelude::*;
+
+use crate::domain::*;
+use crate::rng::GameRng;
+use crate::systems::exploration::SUN_TEMPERATURE;
+use crate::systems::naming::unique_star_name;
+
+/// Radius of the generated stellar neighborhood around Sol
+pub const NEIGHBORHOOD_RADIUS_LY: f64 = 100.0;
+
+/// Most stars `InitializeGalaxy` will generate, Sol included
+pub const MAX_STAR_COUNT: u32 = 10_000;
+
+// Generate the stars around Sol, all at `Detected` level: position, spectral
+// class and luminosity are known, bodies are only generated once a probe
+// arrives. Sol is always the first system, at the origin, and starts explored.
+// Every system gets a name no other system has.
+pub fn generate_galaxy(star_count: u32, rng: &mut GameRng) -> Vec<StarSystem> {
+    let mut systems = Vec::with_capacity(star_count.min(MAX_STAR_COUNT) as usize);
+    if star_count == 0 {
+        return systems;
+    }
+
+    systems.push(StarSystem {
+        id: StarSystemId(rng.next_ulid(0.0)),
+        name: "Sol".to_string(),
+        spectral_class: SpectralClass::G,
+        position: Position::ORIGIN,
+        distance_from_sol: 0.0,
+        mass: 1.0,
+        luminosity: 1.0,
+        temperature: SUN_TEMPERATURE,
+        discovery_level: DiscoveryLevel::Explored,
+        bodies: Vec::new(),
+        belts: Vec::new(),
+        oort_cloud: None,
+    });
+
+    let mut taken = HashSet::from(["Sol".to_string()]);
+    for _ in 1..star_count {
+        let id = StarSystemId(rng.next_ulid(0.0));
+        let name = unique_star_name(id, &mut taken, rng);
+        let position = sample_position(rng, NEIGHBORHOOD_RADIUS_LY);
+        let spectral_class = sample_spectral_class(rng);
+        let (mass, luminosity, temperature) = sample_star(spectral_class, rng);
+
+        systems.push(StarSystem {
+            id,
+            name,
+            spectral_class,
+            position,
+            distance_from_sol: position.distance_to(&Position::ORIGIN),
+            mass,
+            luminosity,
+            temperature,
+            discovery_level: DiscoveryLevel::Detected,
+            bodies: Vec::new(),
+            belts: Vec::new(),
+            oort_cloud: None,
+        });
+    }
+
+    systems
+}
+
+// Uniform within a sphere: cube-root radius keeps density constant with distance
+fn sample_position(rng: &mut GameRng, radius_ly: f64) -> Position {
+    let theta = rng.random::<f64>() * TAU;
+    let cos_phi = rng.random_range(-1.0..=1.0_f64);
+    let sin_phi = (1.0 - cos_phi * cos_phi).sqrt();
+    let r = radius_ly * rng.random::<f64>().cbrt();
+
+    Position {
+        x: r * sin_phi * theta.cos(),
+        y: r * sin_phi * theta.sin(),
+        z: r * cos_phi,
+    }
+}
+
+// Stellar demographics of the solar neighborhood: M dwarfs dominate, O/B are rare
+fn sample_spectral_class(rng: &mut GameRng) -> SpectralClass {
+    let roll = rng.random::<f64>();
+    match roll {
+        r if r < 0.76 => SpectralClass::M,
+        r if r < 0.88 => SpectralClass::K,
+        r if r < 0.96 => SpectralClass::G,
+        r if r < 0.99 => SpectralClass::F,
+        r if r < 0.996 => SpectralClass::A,
+        r if r < 0.999 => SpectralClass::B,
+        _ => SpectralClass::O,
+    }
+}
+
+// Main-sequence mass, luminosity and temperature of a star of the class,
+// relative to Sol apart from the temperature in kelvin. Mass is drawn from the
+// class's range and the rest follows from it.
+fn sample_star(spectral_class: SpectralClass, rng: &mut GameRng) -> (f64, f64, f64) {
+    let (low, high) = match spectral_class {
+        SpectralClass::O => (16.0, 60.0),
+        SpectralClass::B => (2.1, 16.0),
+        SpectralClass::A => (1.4, 2.1),
+        SpectralClass::F => (1.04, 1.4),
+        SpectralClass::G => (0.8, 1.04),
+        SpectralClass::K => (0.45, 0.8),
+        SpectralClass::M => (0.08, 0.45),
+    };
+    let mass: f64 = rng.random_range(low..high);
+    let (luminosity, temperature) = main_sequence(mass);
+
+    (mass, luminosity, temperature)
+}
+
+/// Luminosity relative to Sol and temperature in kelvin of a main-sequence
+/// star of `mass` solar masses, from the mass-luminosity relation, the
+/// mass-radius relation and the Stefan-Boltzmann law
+pub fn main_sequence(mass: f64) -> (f64, f64) {
+    let luminosity = match mass {
+        m if m < 0.43 => 0.23 * m.powf(2.3),
+        m if m < 2.0 => m.powi(4),
+        m if m < 55.0 => 1.4 * m.powf(3.5),
+        m => 32_000.0 * m,
+    };
+    let radius = if mass < 1.0 {
+        mass.powf(0.8)
+    } else {
+        mass.powf(0.57)
+    };
+    let temperature = SUN_TEMPERATURE * (luminosity / (radius * radius)).powf(0.25);
+
+    (luminosity, temperature)
+}
@@ -1,80 +1,563 @@
 pub mod exploration;
+pub mod galaxy;
+pub mod naming;
+pub mod projection;
+pub mod reliability;
 
+pub use projection::{apply, replay};
+
+use crate::rng::GameRng;
 use crate::*;
 
 pub struct ReducerContext {
+    /// Offset the next emitted event will receive
     pub current_offset: u64,
     pub game_time: f64,
+    pub command_id: CommandId,
+    pub rng: GameRng,
+}
+
+impl ReducerContext {
+    /// Build the context for a command reduced at `current_offset`, seeding
+    /// the random stream from the game seed so replays are reproducible.
+    pub fn new(state: &GameState, current_offset: u64) -> Self {
+        let mut rng = GameRng::from_seed_and_offset(state.seed, current_offset);
+        let command_id = CommandId(rng.next_ulid(state.game_time));
+        Self {
+            current_offset,
+            game_time: state.game_time,
+            command_id,
+            rng,
+        }
+    }
+
+    /// Wrap a payload in an envelope, claiming the next offset.
+    pub fn emit(&mut self, game_time: f64, payload: EventPayload) -> EventEnvelope {
+        let envelope = EventEnvelope {
+            offset: self.current_offset,
+            game_time,
+            event_id: EventId(self.rng.next_ulid(game_time)),
+            command_id: self.command_id,
+            payload,
+        };
+        self.current_offset += 1;
+        envelope
+    }
+}
+
+pub type ReducerResult = Result<(GameState, Vec<EventEnvelope>), CommandError>;
+
+/// Events a command has decided so far, each already folded into `state` with
+/// `apply`. Later decisions in the same command see the effects of earlier
+/// events, and replaying the events always rebuilds the same state.
+struct Outcome {
+    state: GameState,
+    events: Vec<EventEnvelope>,
 }
 
-pub type ReducerResult = (GameState, Vec<EventPayload>);
+impl Outcome {
+    fn new(state: GameState) -> Self {
+        Self {
+            state,
+            events: Vec::new(),
+        }
+    }
+
+    fn emit(&mut self, ctx: &mut ReducerContext, game_time: f64, payload: EventPayload) {
+        self.state = apply(std::mem::take(&mut self.state), &payload);
+        self.events.push(ctx.emit(game_time, payload));
+    }
 
+    /// Advance the clock to `time`, skipping empty steps.
+    fn advance_to(&mut self, ctx: &mut ReducerContext, time: f64) {
+        let dt = time - self.state.game_time;
+        if dt > 0.0 {
+            self.emit(ctx, time, EventPayload::TimeAdvanced { dt, new_time: time });
+        }
+    }
+
+    fn finish(self) -> ReducerResult {
+        Ok((self.state, self.events))
+    }
+}
+
+/// Decide which events a command produces; the state only ever changes by
+/// applying those events.
 pub fn reduce(state: GameState, cmd: Command, ctx: ReducerContext) -> ReducerResult {
     match cmd {
         Command::AdvanceTime { dt } => reduce_advance_time(state, dt, ctx),
-        Command::LaunchProbe { target_system_id } => {
-            reduce_launch_probe(state, target_system_id, ctx)
+        Command::LaunchProbe {
+            target_system_id,
+            origin_system_id,
+            probe_class,
+            waypoints,
+        } => reduce_launch_probe(
+            state,
+            target_system_id,
+            origin_system_id,
+            probe_class,
+            waypoints,
+            ctx,
+        ),
+        Command::RedirectProbe {
+            probe_id,
+            target_system_id,
+        } => reduce_redirect_probe(state, probe_id, target_system_id, ctx),
+        Command::RecallProbe { probe_id } => reduce_recall_probe(state, probe_id, ctx),
+        Command::ExploreSystem { system_id } => reduce_explore_system(state, system_id, ctx),
+        Command::InitializeGalaxy { seed, star_count } => {
+            reduce_initialize_galaxy(state, seed, star_count, ctx)
         }
+        Command::RenameEntity {
+            system_id,
+            body_id,
+            name,
+        } => reduce_rename_entity(state, system_id, body_id, name, ctx),
     }
 }
 
-fn reduce_advance_time(state: GameState, dt: f64, _ctx: ReducerContext) -> ReducerResult {
-    let new_time = state.game_time + dt;
-    let mut events = vec![EventPayload::TimeAdvanced { dt, new_time }];
+fn reduce_advance_time(state: GameState, dt: f64, mut ctx: ReducerContext) -> ReducerResult {
+    if !dt.is_finite() || dt < 0.0 {
+        return Err(CommandError::InvalidDuration { dt });
+    }
+
+    let end_time = state.game_time + dt;
+    let mut outcome = Outcome::new(state);
 
-    // Find probes that have arrived
-    let mut arrived_probe_ids = Vec::new();
-    let mut new_state = state.clone();
+    // Fire scheduled actions in time order, stepping the clock to each one so
+    // its events are stamped with the time they actually happened. The cursor
+    // guarantees progress even if a handler leaves its entry in place.
+    // Probes roll for incidents on the way to each action; an incident can
+    // cancel the action, so the schedule is checked again afterwards.
+    let mut cursor = None;
+    let mut rolled_until = outcome.state.game_time;
+    loop {
+        let next = outcome.state.schedule.next_due(cursor, end_time);
+        let until = next.as_ref().map_or(end_time, |entry| entry.due);
+        if until > rolled_until {
+            roll_probe_incidents(&mut outcome, &mut ctx, rolled_until, until);
+            rolled_until = until;
+            continue;
+        }
 
-    for probe in &state.probes_in_flight {
-        if probe.arrival_time <= new_time {
-            // Probe has arrived!
-            arrived_probe_ids.push(probe.id);
+        let Some(entry) = next else {
+            break;
+        };
+        cursor = Some(entry.key());
+        outcome.advance_to(&mut ctx, entry.due);
+        fire_scheduled_action(&mut outcome, &mut ctx, entry);
+    }
 
-            events.push(EventPayload::ProbeArrived {
-                probe_id: probe.id,
-                system_id: probe.target_system_id,
-            });
+    if outcome.events.is_empty() {
+        outcome.emit(
+            &mut ctx,
+            end_time,
+            EventPayload::TimeAdvanced {
+                dt,
+                new_time: end_time,
+            },
+        );
+    } else {
+        outcome.advance_to(&mut ctx, end_time);
+    }
 
-            // Generate the discovered system
-            let system = exploration::generate_system(probe.target_system_id);
+    outcome.finish()
+}
 
-            events.push(EventPayload::SystemDiscovered {
-                system: system.clone(),
-            });
+fn roll_probe_incidents(outcome: &mut Outcome, ctx: &mut ReducerContext, from: f64, until: f64) {
+    let incidents = reliability::roll_incidents(
+        outcome.state.probes_in_flight.values(),
+        from,
+        until,
+        &mut ctx.rng,
+    );
+    for (time, payload) in incidents {
+        outcome.advance_to(ctx, time);
+        outcome.emit(ctx, time, payload);
+    }
+}
 
-            // Add system to state
-            new_state = new_state.with_system_discovered(system);
+fn fire_scheduled_action(outcome: &mut Outcome, ctx: &mut ReducerContext, entry: ScheduledEntry) {
+    match entry.action {
+        ScheduledAction::ProbeArrival { probe_id } => {
+            fire_probe_arrival(outcome, ctx, probe_id, entry.due)
+        }
+        ScheduledAction::SystemExploration { system_id } => {
+            outcome.emit(ctx, entry.due, EventPayload::SystemExplored { system_id })
+        }
+        ScheduledAction::DiscoveryDataArrival { system_id } => {
+            if let Some(data) = outcome.state.data_in_transit.get(&system_id) {
+                let system = data.system.clone();
+                outcome.emit(
+                    ctx,
+                    entry.due,
+                    EventPayload::DiscoveryDataReceived { system },
+                );
+            }
         }
     }
+}
 
-    // Remove arrived probes from state
-    new_state = new_state.with_probes_removed(&arrived_probe_ids);
+fn fire_probe_arrival(
+    outcome: &mut Outcome,
+    ctx: &mut ReducerContext,
+    probe_id: ProbeId,
+    arrival_time: f64,
+) {
+    let Some(probe) = outcome.state.probes_in_flight.get(&probe_id).cloned() else {
+        return;
+    };
 
-    // Update game time
-    new_state.game_time = new_time;
+    outcome.emit(
+        ctx,
+        arrival_time,
+        EventPayload::ProbeArrived {
+            probe_id: probe.id,
+            system_id: probe.target_system_id,
+        },
+    );
 
-    (new_state, events)
+    // Scan the system if this is the first probe to reach it, and send the
+    // results home
+    if let Some(detected) = outcome.state.systems.get(&probe.target_system_id)
+        && detected.discovery_level == DiscoveryLevel::Detected
+        && !outcome.state.data_in_transit.contains_key(&detected.id)
+    {
+        let sensor_resolution = probe.probe_class.spec().sensor_resolution * probe.condition;
+        let system = exploration::generate_system(
+            detected,
+            sensor_resolution,
+            |name| outcome.state.is_name_taken(name),
+            &mut ctx.rng,
+        );
+        let received_at = arrival_time + exploration::signal_delay(&system.position);
+        outcome.emit(
+            ctx,
+            arrival_time,
+            EventPayload::DiscoveryDataTransmitted {
+                system,
+                received_at,
+            },
+        );
+    }
+
+    // Move on to the next waypoint, or retire if it is out of reach
+    let Some(&next_system_id) = probe.waypoints.first() else {
+        return;
+    };
+    let Some(probe) = outcome.state.probes_in_flight.get(&probe_id) else {
+        return;
+    };
+    let payload = match plot_course(&outcome.state, probe, next_system_id, arrival_time) {
+        Ok(course) => EventPayload::ProbeDeparted { probe_id, course },
+        Err(_) => EventPayload::ProbeRetired {
+            probe_id,
+            system_id: probe.target_system_id,
+        },
+    };
+    outcome.emit(ctx, arrival_time, payload);
 }
 
 fn reduce_launch_probe(
     state: GameState,
     target_system_id: StarSystemId,
-    ctx: ReducerContext,
+    origin_system_id: Option<StarSystemId>,
+    probe_class: ProbeClass,
+    waypoints: Vec<StarSystemId>,
+    mut ctx: ReducerContext,
+) -> ReducerResult {
+    let mut previous = target_system_id;
+    for &system_id in &waypoints {
+        if !state.systems.contains_key(&system_id) {
+            return Err(CommandError::UnknownSystem { system_id });
+        }
+        if system_id == previous {
+            return Err(CommandError::RepeatedWaypoint { system_id });
+        }
+        previous = system_id;
+    }
+
+    let Some(target) = state.systems.get(&target_system_id) else {
+        return Err(CommandError::UnknownSystem {
+            system_id: target_system_id,
+        });
+    };
+    let origin_position = match origin_system_id {
+        None => Position::ORIGIN,
+        Some(system_id) => {
+            let origin = state
+                .systems
+                .get(&system_id)
+                .ok_or(CommandError::UnknownSystem { system_id })?;
+            if !origin.is_discovered() || system_id == target_system_id {
+                return Err(CommandError::InvalidOrigin { system_id });
+            }
+            origin.position
+        }
+    };
+    // Only the leg being flown is exclusive. Itineraries may share later
+    // waypoints with other probes: whichever arrives second finds the system
+    // already scanned and moves on, and courses can still change before then.
+    if state
+        .probes_in_flight
+        .values()
+        .any(|p| p.target_system_id == target_system_id)
+    {
+        return Err(CommandError::DuplicateProbe {
+            system_id: target_system_id,
+        });
+    }
+
+    // Whatever the origin, a probe already at its target would arrive the
+    // moment it launched
+    let distance_ly = origin_position.distance_to(&target.position);
+    if distance_ly <= 0.0 {
+        return Err(CommandError::TargetIsOrigin {
+            system_id: target_system_id,
+        });
+    }
+    let max_range_ly = probe_class.spec().max_range_ly;
+    if distance_ly > max_range_ly {
+        return Err(CommandError::OutOfRange {
+            system_id: target_system_id,
+            probe_class,
+            distance_ly,
+            max_range_ly,
+        });
+    }
+
+    let travel_time = exploration::calculate_travel_time(distance_ly, probe_class);
+    let now = ctx.game_time;
+    let arrival_time = now + travel_time.galaxy_frame_hours;
+
+    let probe_id = ProbeId(ctx.rng.next_ulid(now));
+
+    let mut outcome = Outcome::new(state);
+    outcome.emit(
+        &mut ctx,
+        now,
+        EventPayload::ProbeLaunched {
+            probe_id,
+            probe_class,
+            target_system_id,
+            origin_system_id,
+            distance_ly,
+            eta: arrival_time,
+            ship_frame_hours: travel_time.ship_frame_hours,
+            waypoints,
+        },
+    );
+    outcome.finish()
+}
+
+fn reduce_redirect_probe(
+    state: GameState,
+    probe_id: ProbeId,
+    target_system_id: StarSystemId,
+    mut ctx: ReducerContext,
+) -> ReducerResult {
+    let probe = state
+        .probes_in_flight
+        .get(&probe_id)
+        .ok_or(CommandError::UnknownProbe { probe_id })?;
+    if state
+        .probes_in_flight
+        .values()
+        .any(|p| p.id != probe_id && p.target_system_id == target_system_id)
+    {
+        return Err(CommandError::DuplicateProbe {
+            system_id: target_system_id,
+        });
+    }
+    let course = plot_course(&state, probe, target_system_id, ctx.game_time)?;
+
+    let now = ctx.game_time;
+    let mut outcome = Outcome::new(state);
+    outcome.emit(
+        &mut ctx,
+        now,
+        EventPayload::ProbeRedirected { probe_id, course },
+    );
+    outcome.finish()
+}
+
+fn reduce_recall_probe(
+    state: GameState,
+    probe_id: ProbeId,
+    mut ctx: ReducerContext,
 ) -> ReducerResult {
-    // Mock distance calculation - in real game, this would come from system coords
-    let distance_ly = 4.37; // Alpha Centauri distance
-    let travel_time = exploration::calculate_travel_time(distance_ly);
-    let arrival_time = ctx.game_time + travel_time;
+    let probe = state
+        .probes_in_flight
+        .get(&probe_id)
+        .ok_or(CommandError::UnknownProbe { probe_id })?;
+    let home_system_id = probe
+        .origin_system_id
+        .or_else(|| state.sol().map(|sol| sol.id))
+        .ok_or(CommandError::NoReturnSystem { probe_id })?;
+    let course = plot_course(&state, probe, home_system_id, ctx.game_time)?;
 
-    // Add probe to state
-    let (new_state, probe_id) = state.with_probe_launched(target_system_id, arrival_time);
+    let now = ctx.game_time;
+    let mut outcome = Outcome::new(state);
+    outcome.emit(
+        &mut ctx,
+        now,
+        EventPayload::ProbeRecalled { probe_id, course },
+    );
+    outcome.finish()
+}
 
-    let events = vec![EventPayload::ProbeLaunched {
-        probe_id,
+// Plot a new leg from where `probe` is at `now` to `target_system_id`. The
+// probe brakes and sets off again from rest, so the leg follows the same
+// drive model as a launch.
+fn plot_course(
+    state: &GameState,
+    probe: &ProbeInFlight,
+    target_system_id: StarSystemId,
+    now: f64,
+) -> Result<ProbeCourse, CommandError> {
+    if probe.target_system_id == target_system_id {
+        return Err(CommandError::AlreadyEnRoute {
+            probe_id: probe.id,
+            system_id: target_system_id,
+        });
+    }
+    let target = state
+        .systems
+        .get(&target_system_id)
+        .ok_or(CommandError::UnknownSystem {
+            system_id: target_system_id,
+        })?;
+    let current_target =
+        state
+            .systems
+            .get(&probe.target_system_id)
+            .ok_or(CommandError::UnknownSystem {
+                system_id: probe.target_system_id,
+            })?;
+
+    let departure = exploration::probe_position(probe, &current_target.position, now);
+    let distance_ly = departure.distance_to(&target.position);
+    let range_left_ly = probe.range_left_ly(&departure);
+    if distance_ly > range_left_ly {
+        return Err(CommandError::RangeExhausted {
+            probe_id: probe.id,
+            system_id: target_system_id,
+            distance_ly,
+            range_left_ly,
+        });
+    }
+
+    let travel_time = exploration::calculate_travel_time(distance_ly, probe.probe_class);
+    Ok(ProbeCourse {
         target_system_id,
-        eta: arrival_time,
-    }];
+        departure,
+        distance_ly,
+        eta: now + travel_time.galaxy_frame_hours,
+        ship_frame_hours: travel_time.ship_frame_hours,
+    })
+}
+
+fn reduce_explore_system(
+    state: GameState,
+    system_id: StarSystemId,
+    mut ctx: ReducerContext,
+) -> ReducerResult {
+    let system = state
+        .systems
+        .get(&system_id)
+        .ok_or(CommandError::UnknownSystem { system_id })?;
+    if system.discovery_level != DiscoveryLevel::Scanned {
+        return Err(CommandError::NotExplorable {
+            system_id,
+            discovery_level: system.discovery_level,
+        });
+    }
+    if state
+        .schedule
+        .contains(&ScheduledAction::SystemExploration { system_id })
+    {
+        return Err(CommandError::ExplorationInProgress { system_id });
+    }
+
+    let now = ctx.game_time;
+    let mut outcome = Outcome::new(state);
+    outcome.emit(
+        &mut ctx,
+        now,
+        EventPayload::ExplorationStarted {
+            system_id,
+            completes_at: now + exploration::EXPLORATION_DURATION_HOURS,
+        },
+    );
+    outcome.finish()
+}
+
+fn reduce_initialize_galaxy(
+    state: GameState,
+    seed: u64,
+    star_count: u32,
+    mut ctx: ReducerContext,
+) -> ReducerResult {
+    if !state.systems.is_empty() {
+        return Err(CommandError::GalaxyAlreadyInitialized);
+    }
+    if !(1..=galaxy::MAX_STAR_COUNT).contains(&star_count) {
+        return Err(CommandError::InvalidStarCount { star_count });
+    }
+
+    // The galaxy depends only on its own seed, not on when it was created
+    let mut rng = GameRng::from_seed_and_offset(seed, 0);
+    let systems = galaxy::generate_galaxy(star_count, &mut rng);
+
+    let now = ctx.game_time;
+    let mut outcome = Outcome::new(state);
+    outcome.emit(
+        &mut ctx,
+        now,
+        EventPayload::GalaxyInitialized { seed, systems },
+    );
+    outcome.finish()
+}
+
+fn reduce_rename_entity(
+    state: GameState,
+    system_id: StarSystemId,
+    body_id: Option<CelestialBodyId>,
+    name: String,
+    mut ctx: ReducerContext,
+) -> ReducerResult {
+    let system = state
+        .systems
+        .get(&system_id)
+        .ok_or(CommandError::UnknownSystem { system_id })?;
+    // Bodies the player has not seen cannot be named
+    if let Some(body_id) = body_id {
+        let known = system.known_bodies().any(|body| body.id == body_id)
+            || system.known_belts().any(|belt| belt.id == body_id);
+        if !known {
+            return Err(CommandError::UnknownBody { system_id, body_id });
+        }
+    }
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(CommandError::BlankName);
+    }
+    // Names the player has not come across yet cannot give away that
+    // something goes by them
+    if state.is_name_known(&name) {
+        return Err(CommandError::NameTaken { name });
+    }
 
-    (new_state, events)
+    let now = ctx.game_time;
+    let mut outcome = Outcome::new(state);
+    outcome.emit(
+        &mut ctx,
+        now,
+        EventPayload::EntityRenamed {
+            system_id,
+            body_id,
+            name,
+        },
+    );
+    outcome.finish()
 }
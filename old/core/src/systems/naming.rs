@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+
+use rand::prelude::*;
+
+use crate::domain::*;
+use crate::rng::GameRng;
+
+/// Fresh names tried for a star before it falls back to a catalog designation
+const NAME_ATTEMPTS: usize = 8;
+
+const ONSETS: &[&str] = &[
+    "b", "c", "d", "f", "g", "h", "k", "l", "m", "n", "p", "r", "s", "t", "v", "z", "br", "ch",
+    "dr", "kr", "sh", "st", "th", "tr",
+];
+const VOWELS: &[&str] = &[
+    "a", "a", "e", "e", "i", "o", "o", "u", "ae", "ai", "au", "ei",
+];
+const CODAS: &[&str] = &["", "", "", "", "l", "m", "n", "r", "s", "th", "x"];
+
+// A name for the star of `id` that no other system has, recording it in
+// `taken`. Generated names are pronounceable syllable strings; should the
+// generator keep colliding, the star gets a catalog designation instead.
+pub fn unique_star_name(
+    id: StarSystemId,
+    taken: &mut HashSet<String>,
+    rng: &mut GameRng,
+) -> String {
+    for _ in 0..NAME_ATTEMPTS {
+        let name = star_name(rng);
+        if taken.insert(name.clone()) {
+            return name;
+        }
+    }
+
+    let mut name = catalog_name(id);
+    if taken.contains(&name) {
+        // Ids are unique even where their catalog suffixes collide
+        name = format!("SYS-{}", id.0);
+    }
+    taken.insert(name.clone());
+    name
+}
+
+// `name` if nothing goes by it yet, otherwise `name` numbered from 2 up until
+// it is free. Generated body names only collide with names players chose.
+pub fn unique_body_name(name: String, is_taken: &impl Fn(&str) -> bool) -> String {
+    if !is_taken(&name) {
+        return name;
+    }
+    (2..)
+        .map(|number| format!("{name} ({number})"))
+        .find(|numbered| !is_taken(numbered))
+        .expect("a free number")
+}
+
+// Two or three syllables of a consonant and a vowel, the last one closed by a
+// consonant now and then. Only the first may open on its vowel, so vowels
+// never pile up.
+fn star_name(rng: &mut GameRng) -> String {
+    let syllables = if rng.random_bool(0.7) { 2 } else { 3 };
+    let mut name = String::new();
+    for i in 0..syllables {
+        if i > 0 || rng.random_bool(0.8) {
+            name.push_str(ONSETS.choose(rng).unwrap());
+        }
+        name.push_str(VOWELS.choose(rng).unwrap());
+    }
+    name.push_str(CODAS.choose(rng).unwrap());
+
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+// Catalog-style designation derived from the id, as the Godot generator does
+pub fn catalog_name(id: StarSystemId) -> String {
+    let id = id.0.to_string();
+    match id.as_bytes()[id.len() - 1] % 5 {
+        0 => format!("HD-{}", &id[20..]),
+        1 => format!("Gliese-{}", &id[20..]),
+        2 => format!("Kepler-{}", &id[20..]),
+        3 => format!("LHS-{}", &id[20..]),
+        _ => format!("2MASS-{}", &id[18..]),
+    }
+}
+
+// Component `index` of a multiple star: A for the primary, then B, C...
+pub fn star_letter(index: usize) -> char {
+    (b'A' + index as u8) as char
+}
+
+// The `index`th planet around a host: b for the first, as the host itself
+// takes a
+pub fn planet_letter(index: usize) -> char {
+    (b'b' + index as u8) as char
+}
+
+// Roman numeral for `number`, as moons are numbered
+pub fn roman_numeral(mut number: usize) -> String {
+    const NUMERALS: [(usize, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+
+    let mut numeral = String::new();
+    for (value, symbol) in NUMERALS {
+        while number >= value {
+            numeral.push_str(symbol);
+            number -= value;
+        }
+    }
+    numeral
+}
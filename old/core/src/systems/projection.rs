@@ -0,0 +1,81 @@
+use crate::*;
+
+/// Apply a single event to the state. This is the only place events change
+/// `GameState`; both live reduction and replay from a stored log go through it.
+pub fn apply(state: GameState, event: &EventPayload) -> GameState {
+    match event {
+        EventPayload::TimeAdvanced { new_time, .. } => state.with_game_time(*new_time),
+        EventPayload::ProbeLaunched {
+            probe_id,
+            probe_class,
+            target_system_id,
+            origin_system_id,
+            distance_ly,
+            eta,
+            ship_frame_hours,
+            waypoints,
+        } => {
+            let departure = origin_system_id
+                .and_then(|system_id| state.systems.get(&system_id))
+                .map_or(Position::ORIGIN, |origin| origin.position);
+            let probe = ProbeInFlight {
+                id: *probe_id,
+                probe_class: *probe_class,
+                target_system_id: *target_system_id,
+                origin_system_id: *origin_system_id,
+                launched_at: state.game_time,
+                departure,
+                departed_at: state.game_time,
+                distance_ly: *distance_ly,
+                arrival_time: *eta,
+                ship_frame_hours: *ship_frame_hours,
+                condition: 1.0,
+                waypoints: waypoints.clone(),
+                distance_flown_ly: 0.0,
+            };
+            state.with_probe_launched(probe)
+        }
+        EventPayload::ProbeRedirected { probe_id, course } => {
+            state.with_probe_course_changed(*probe_id, course)
+        }
+        EventPayload::ProbeRecalled { probe_id, course } => {
+            state.with_probe_recalled(*probe_id, course)
+        }
+        EventPayload::ProbeDeparted { probe_id, course } => {
+            state.with_probe_departed(*probe_id, course)
+        }
+        EventPayload::ProbeRetired { probe_id, .. } => state.with_probes_removed(&[*probe_id]),
+        EventPayload::ProbeDamaged {
+            probe_id,
+            condition,
+        } => state.with_probe_damaged(*probe_id, *condition),
+        EventPayload::ProbeLost { probe_id } => state.with_probes_removed(&[*probe_id]),
+        EventPayload::ProbeArrived { probe_id, .. } => state.with_probe_arrived(*probe_id),
+        EventPayload::DiscoveryDataTransmitted {
+            system,
+            received_at,
+        } => state.with_discovery_data_transmitted(system.clone(), *received_at),
+        EventPayload::DiscoveryDataReceived { system } => {
+            state.with_discovery_data_received(system.clone())
+        }
+        EventPayload::ExplorationStarted {
+            system_id,
+            completes_at,
+        } => state.with_exploration_started(*system_id, *completes_at),
+        EventPayload::SystemExplored { system_id } => state.with_system_explored(*system_id),
+        EventPayload::GalaxyInitialized { systems, .. } => state.with_galaxy_initialized(systems),
+        EventPayload::EntityRenamed {
+            system_id,
+            body_id,
+            name,
+        } => state.with_entity_renamed(*system_id, *body_id, name),
+    }
+}
+
+/// Fold a sequence of events into `state`, in order.
+pub fn replay<'a>(
+    state: GameState,
+    events: impl IntoIterator<Item = &'a EventPayload>,
+) -> GameState {
+    events.into_iter().fold(state, apply)
+}
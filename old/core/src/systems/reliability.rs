@@ -0,0 +1,70 @@
+use rand::prelude::*;
+
+use crate::domain::*;
+use crate::events::EventPayload;
+use crate::rng::GameRng;
+use crate::travel::HOURS_PER_YEAR;
+
+/// Share of incidents that destroy the probe outright; the rest damage it
+pub const LOSS_SHARE: f64 = 0.25;
+
+// Roll for incidents on every probe in flight between `from` and `until`.
+// Incidents arrive at a constant rate on the probe's own clock, each after an
+// exponential wait from the last. As the wait is memoryless, how the interval
+// is split across commands does not change the odds. A probe may be damaged
+// several times in one interval, each time from its condition after the last
+// incident, until an incident destroys it. Returns the incidents in time
+// order.
+pub fn roll_incidents<'a>(
+    probes: impl IntoIterator<Item = &'a ProbeInFlight>,
+    from: f64,
+    until: f64,
+    rng: &mut GameRng,
+) -> Vec<(f64, EventPayload)> {
+    let mut incidents = Vec::new();
+    for probe in probes {
+        let start = from.max(probe.launched_at);
+        let rate = incident_rate_per_hour(probe);
+        if until <= start || rate <= 0.0 {
+            continue;
+        }
+
+        let mut time = start;
+        let mut condition = probe.condition;
+        loop {
+            // Exponential wait until the next incident
+            time += -(1.0 - rng.random::<f64>()).ln() / rate;
+            if time >= until {
+                break;
+            }
+
+            if rng.random::<f64>() < LOSS_SHARE {
+                incidents.push((time, EventPayload::ProbeLost { probe_id: probe.id }));
+                break;
+            }
+            condition *= rng.random_range(0.5..0.9);
+            incidents.push((
+                time,
+                EventPayload::ProbeDamaged {
+                    probe_id: probe.id,
+                    condition,
+                },
+            ));
+        }
+    }
+
+    incidents.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    incidents
+}
+
+// Galaxy-frame incident rate: time dilation slows the probe's wear as seen
+// from the galaxy
+fn incident_rate_per_hour(probe: &ProbeInFlight) -> f64 {
+    let per_ship_hour = probe.probe_class.spec().incidents_per_year / HOURS_PER_YEAR;
+    let galaxy_frame_hours = probe.arrival_time - probe.departed_at;
+    if galaxy_frame_hours > 0.0 {
+        per_ship_hour * probe.ship_frame_hours / galaxy_frame_hours
+    } else {
+        per_ship_hour
+    }
+}
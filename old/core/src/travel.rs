@@ -0,0 +1,140 @@
+/// Game hours in a Julian year, for converting light-years at a fraction of c
+pub const HOURS_PER_YEAR: f64 = 8_766.0;
+
+/// One standard gravity in light-years per year squared
+pub const STANDARD_GRAVITY_LY_PER_YEAR2: f64 = 1.0323;
+
+/// How a vessel's drive moves it between stars: a constant proper
+/// acceleration up to a cruise speed, a coast, then a constant proper
+/// deceleration to rest at the destination. Shared by probes and ships.
+///
+/// Either the accelerations must be finite or the speed below c. An infinite
+/// acceleration reaches cruise speed instantly; a maximum speed of 1 lets the
+/// drive burn all the way to the turnaround point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriveProfile {
+    /// Proper acceleration in standard gravities
+    pub acceleration_g: f64,
+    /// Cruise speed as a fraction of the speed of light, in `(0, 1]`
+    pub max_speed_c: f64,
+    /// Proper deceleration in standard gravities
+    pub deceleration_g: f64,
+}
+
+/// Elapsed time of a trip, in game hours, as measured by a clock at rest in
+/// the galaxy and by a clock on board.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TravelTime {
+    pub galaxy_frame_hours: f64,
+    pub ship_frame_hours: f64,
+}
+
+impl TravelTime {
+    pub const ZERO: TravelTime = TravelTime {
+        galaxy_frame_hours: 0.0,
+        ship_frame_hours: 0.0,
+    };
+}
+
+/// Time taken to travel `distance_ly` from rest to rest with `drive`.
+///
+/// Works in rapidity `φ`, with `c = 1`, light-years and years. Under a
+/// constant proper acceleration `a` from rest, reaching rapidity `φ` takes
+/// `sinh(φ) / a` galaxy time, `φ / a` ship time and covers `(cosh(φ) - 1) / a`.
+/// When the burns alone would overshoot, the drive turns around at the peak
+/// rapidity where they exactly cover the distance, and never coasts.
+pub fn travel_time(distance_ly: f64, drive: &DriveProfile) -> TravelTime {
+    if distance_ly <= 0.0 {
+        return TravelTime::ZERO;
+    }
+
+    let plan = FlightPlan::new(distance_ly, drive);
+    let ship_coast = if plan.coast_ly > 0.0 {
+        plan.coast_ly / plan.peak_rapidity.sinh()
+    } else {
+        0.0
+    };
+    let ship_years = plan.peak_rapidity * (1.0 / plan.accel + 1.0 / plan.decel) + ship_coast;
+
+    TravelTime {
+        galaxy_frame_hours: plan.galaxy_years() * HOURS_PER_YEAR,
+        ship_frame_hours: ship_years * HOURS_PER_YEAR,
+    }
+}
+
+/// How far along a `distance_ly` trip with `drive` a vessel is once
+/// `elapsed_hours` of galaxy time have passed since departure.
+///
+/// Under constant proper acceleration `a` from rest, the distance covered
+/// after galaxy time `t` is `(sqrt(1 + (a t)²) - 1) / a`; the deceleration
+/// burn mirrors that from the far end.
+pub fn distance_covered(distance_ly: f64, drive: &DriveProfile, elapsed_hours: f64) -> f64 {
+    if distance_ly <= 0.0 || elapsed_hours <= 0.0 {
+        return 0.0;
+    }
+
+    let plan = FlightPlan::new(distance_ly, drive);
+    let t = elapsed_hours / HOURS_PER_YEAR;
+    let total = plan.galaxy_years();
+    if t >= total {
+        return distance_ly;
+    }
+
+    let accel_years = plan.peak_rapidity.sinh() / plan.accel;
+    let coast_years = plan.coast_years();
+    if t < accel_years {
+        ((1.0 + (plan.accel * t).powi(2)).sqrt() - 1.0) / plan.accel
+    } else if t < accel_years + coast_years {
+        let accel_ly = (plan.peak_rapidity.cosh() - 1.0) / plan.accel;
+        accel_ly + plan.peak_rapidity.tanh() * (t - accel_years)
+    } else {
+        let remaining = total - t;
+        distance_ly - ((1.0 + (plan.decel * remaining).powi(2)).sqrt() - 1.0) / plan.decel
+    }
+}
+
+/// Shape of a rest-to-rest trip: proper accelerations in ly/yr², the
+/// rapidity reached at the end of the acceleration burn, and the distance
+/// covered coasting at it.
+struct FlightPlan {
+    accel: f64,
+    decel: f64,
+    peak_rapidity: f64,
+    coast_ly: f64,
+}
+
+impl FlightPlan {
+    fn new(distance_ly: f64, drive: &DriveProfile) -> Self {
+        let accel = drive.acceleration_g * STANDARD_GRAVITY_LY_PER_YEAR2;
+        let decel = drive.deceleration_g * STANDARD_GRAVITY_LY_PER_YEAR2;
+        // Distance per unit of `cosh(φ) - 1` covered by both burns together
+        let burn_reach = 1.0 / accel + 1.0 / decel;
+
+        let cruise_rapidity = drive.max_speed_c.atanh();
+        let burn_distance = (cruise_rapidity.cosh() - 1.0) * burn_reach;
+        let (peak_rapidity, coast_ly) = if burn_distance <= distance_ly {
+            (cruise_rapidity, distance_ly - burn_distance)
+        } else {
+            ((1.0 + distance_ly / burn_reach).acosh(), 0.0)
+        };
+
+        Self {
+            accel,
+            decel,
+            peak_rapidity,
+            coast_ly,
+        }
+    }
+
+    fn coast_years(&self) -> f64 {
+        if self.coast_ly > 0.0 {
+            self.coast_ly / self.peak_rapidity.tanh()
+        } else {
+            0.0
+        }
+    }
+
+    fn galaxy_years(&self) -> f64 {
+        self.peak_rapidity.sinh() * (1.0 / self.accel + 1.0 / self.decel) + self.coast_years()
+    }
+}
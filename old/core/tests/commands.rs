@@ -0,0 +1,127 @@
+use outpost_3_core::systems::{self, ReducerContext};
+use outpost_3_core::*;
+
+/// Reduces commands one after another, keeping the state it had when one is
+/// rejected, the way an FFI session does
+struct Game {
+    state: GameState,
+    events: Vec<EventEnvelope>,
+}
+
+impl Game {
+    fn new() -> Self {
+        let mut game = Self {
+            state: GameState::new().with_seed(5),
+            events: Vec::new(),
+        };
+        game.apply(Command::InitializeGalaxy {
+            seed: 11,
+            star_count: 20,
+        })
+        .unwrap();
+        game
+    }
+
+    fn apply(&mut self, command: Command) -> Result<(), CommandError> {
+        let ctx = ReducerContext::new(&self.state, self.events.len() as u64);
+        let (state, events) = systems::reduce(self.state.clone(), command, ctx)?;
+        self.state = state;
+        self.events.extend(events);
+        Ok(())
+    }
+
+    // Apply a command that must be refused, checking it changed nothing
+    fn reject(&mut self, command: Command) -> CommandError {
+        let state = serde_json::to_value(&self.state).unwrap();
+        let event_count = self.events.len();
+
+        let error = self.apply(command).unwrap_err();
+        assert_eq!(serde_json::to_value(&self.state).unwrap(), state);
+        assert_eq!(self.events.len(), event_count);
+        error
+    }
+
+    fn nearest_star(&self) -> StarSystemId {
+        self.state
+            .systems
+            .values()
+            .filter(|system| system.distance_from_sol > 0.0)
+            .min_by(|a, b| a.distance_from_sol.total_cmp(&b.distance_from_sol))
+            .unwrap()
+            .id
+    }
+}
+
+fn launch(target_system_id: StarSystemId) -> Command {
+    Command::LaunchProbe {
+        target_system_id,
+        origin_system_id: None,
+        probe_class: ProbeClass::Survey,
+        waypoints: Vec::new(),
+    }
+}
+
+#[test]
+fn time_only_advances_by_finite_non_negative_steps() {
+    let mut game = Game::new();
+
+    assert_eq!(
+        game.reject(Command::AdvanceTime { dt: -1.0 }),
+        CommandError::InvalidDuration { dt: -1.0 }
+    );
+    for dt in [f64::NAN, f64::INFINITY] {
+        match game.reject(Command::AdvanceTime { dt }) {
+            CommandError::InvalidDuration { dt: rejected } => {
+                assert_eq!(rejected.to_bits(), dt.to_bits())
+            }
+            other => panic!("expected an invalid duration, got {other:?}"),
+        }
+    }
+
+    game.apply(Command::AdvanceTime { dt: 0.0 }).unwrap();
+    assert_eq!(game.state.game_time, 0.0);
+}
+
+#[test]
+fn probes_cannot_target_unknown_systems() {
+    let mut game = Game::new();
+    let unknown = StarSystemId::new();
+
+    assert_eq!(
+        game.reject(launch(unknown)),
+        CommandError::UnknownSystem { system_id: unknown }
+    );
+}
+
+#[test]
+fn only_one_probe_at_a_time_heads_to_a_system() {
+    let mut game = Game::new();
+    let target = game.nearest_star();
+    game.apply(launch(target)).unwrap();
+
+    assert_eq!(
+        game.reject(launch(target)),
+        CommandError::DuplicateProbe { system_id: target }
+    );
+    assert_eq!(game.state.probes_in_flight.len(), 1);
+}
+
+#[test]
+fn probes_cannot_launch_to_where_they_start() {
+    let mut game = Game::new();
+    let sol = game.state.sol().unwrap().id;
+
+    assert_eq!(
+        game.reject(launch(sol)),
+        CommandError::TargetIsOrigin { system_id: sol }
+    );
+    assert_eq!(
+        game.reject(Command::LaunchProbe {
+            target_system_id: sol,
+            origin_system_id: Some(sol),
+            probe_class: ProbeClass::Survey,
+            waypoints: Vec::new(),
+        }),
+        CommandError::InvalidOrigin { system_id: sol }
+    );
+}
@@ -0,0 +1,46 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use outpost_3_core::*;
+use ulid::Ulid;
+
+/// Scratch directory for a test, removed again when dropped
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("outpost-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A `TimeAdvanced` event at `offset`, each one 1.5 hours on from the last
+pub fn event(offset: u64) -> EventEnvelope {
+    let game_time = (offset + 1) as f64 * 1.5;
+    EventEnvelope {
+        offset,
+        game_time,
+        event_id: EventId(Ulid::from_parts(offset, 1)),
+        command_id: CommandId(Ulid::from_parts(offset, 2)),
+        payload: EventPayload::TimeAdvanced {
+            dt: 1.5,
+            new_time: game_time,
+        },
+    }
+}
+
+pub fn events(offsets: std::ops::Range<u64>) -> Vec<EventEnvelope> {
+    offsets.map(event).collect()
+}
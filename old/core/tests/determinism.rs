@@ -0,0 +1,149 @@
+use outpost_3_core::systems::{self, ReducerContext, replay};
+use outpost_3_core::*;
+use serde_json::Value;
+
+/// Reduces commands one after another, the way an FFI session does
+struct Game {
+    state: GameState,
+    events: Vec<EventEnvelope>,
+}
+
+impl Game {
+    fn new(seed: u64) -> Self {
+        Self {
+            state: GameState::new().with_seed(seed),
+            events: Vec::new(),
+        }
+    }
+
+    fn nearest_undiscovered(&self) -> StarSystemId {
+        self.state
+            .systems
+            .values()
+            .filter(|system| system.name != "Sol" && !system.is_discovered())
+            .filter(|system| {
+                let targeted = |probe: &ProbeInFlight| probe.target_system_id == system.id;
+                !self.state.probes_in_flight.values().any(targeted)
+            })
+            .min_by(|a, b| a.distance_from_sol.total_cmp(&b.distance_from_sol))
+            .unwrap()
+            .id
+    }
+
+    fn apply(&mut self, command: Command) {
+        let ctx = ReducerContext::new(&self.state, self.events.len() as u64);
+        let (state, events) =
+            systems::reduce(self.state.clone(), command, ctx).expect("command rejected");
+        self.state = state;
+        self.events.extend(events);
+    }
+}
+
+// Create a galaxy, send a probe to the nearest star and let it fly, scan and
+// report back. Returns the final state and every event emitted, as JSON.
+fn play(seed: u64) -> (Value, Value) {
+    let mut game = Game::new(seed);
+    game.apply(Command::InitializeGalaxy {
+        seed: 11,
+        star_count: 40,
+    });
+
+    let target = game
+        .state
+        .systems
+        .values()
+        .filter(|system| system.name != "Sol")
+        .min_by(|a, b| a.distance_from_sol.total_cmp(&b.distance_from_sol))
+        .unwrap()
+        .id;
+    game.apply(Command::LaunchProbe {
+        target_system_id: target,
+        origin_system_id: None,
+        probe_class: ProbeClass::default(),
+        waypoints: Vec::new(),
+    });
+    for _ in 0..40 {
+        game.apply(Command::AdvanceTime { dt: HOURS_PER_YEAR });
+    }
+
+    (
+        serde_json::to_value(&game.state).unwrap(),
+        serde_json::to_value(&game.events).unwrap(),
+    )
+}
+
+#[test]
+fn same_seed_and_commands_give_the_same_game() {
+    let (state, events) = play(42);
+    let (again_state, again_events) = play(42);
+
+    assert_eq!(state, again_state);
+    assert_eq!(events, again_events);
+}
+
+#[test]
+fn game_seed_drives_event_ids() {
+    let (_, events) = play(42);
+    let (_, other_events) = play(43);
+
+    assert_ne!(events, other_events);
+}
+
+#[test]
+fn replaying_the_events_rebuilds_the_live_state() {
+    let mut game = Game::new(42);
+    game.apply(Command::InitializeGalaxy {
+        seed: 11,
+        star_count: 40,
+    });
+
+    // Scan the nearest star, then explore and rename it
+    let scanned = game.nearest_undiscovered();
+    game.apply(Command::LaunchProbe {
+        target_system_id: scanned,
+        origin_system_id: None,
+        probe_class: ProbeClass::DeepSurvey,
+        waypoints: Vec::new(),
+    });
+    while !game.state.systems[&scanned].is_discovered() {
+        game.apply(Command::AdvanceTime {
+            dt: 10.0 * HOURS_PER_YEAR,
+        });
+    }
+    game.apply(Command::ExploreSystem { system_id: scanned });
+    game.apply(Command::RenameEntity {
+        system_id: scanned,
+        body_id: None,
+        name: "Halcyon".to_string(),
+    });
+
+    // Send a probe on from there and change its course mid-flight
+    let first = game.nearest_undiscovered();
+    game.apply(Command::LaunchProbe {
+        target_system_id: first,
+        origin_system_id: Some(scanned),
+        probe_class: ProbeClass::Survey,
+        waypoints: Vec::new(),
+    });
+    game.apply(Command::AdvanceTime { dt: HOURS_PER_YEAR });
+    let probe_id = *game.state.probes_in_flight.keys().next().unwrap();
+    let second = game.nearest_undiscovered();
+    game.apply(Command::RedirectProbe {
+        probe_id,
+        target_system_id: second,
+    });
+    while !game.state.probes_in_flight.is_empty() {
+        game.apply(Command::AdvanceTime {
+            dt: 10.0 * HOURS_PER_YEAR,
+        });
+    }
+
+    let replayed = replay(
+        GameState::new().with_seed(42),
+        game.events.iter().map(|event| &event.payload),
+    );
+    assert_eq!(
+        serde_json::to_value(&replayed).unwrap(),
+        serde_json::to_value(&game.state).unwrap()
+    );
+}
@@ -0,0 +1,217 @@
+mod common;
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use common::{TempDir, event, events};
+use outpost_3_core::persistence::*;
+
+fn offsets(store: &FileEventStore, from: u64) -> Vec<u64> {
+    store
+        .read_from(from)
+        .unwrap()
+        .map(|event| event.unwrap().offset)
+        .collect()
+}
+
+fn segments(dir: &TempDir) -> Vec<PathBuf> {
+    let mut segments: Vec<_> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    segments.sort();
+    segments
+}
+
+// Config fitting two records of `event` into each segment, but not three
+fn two_per_segment(dir: &TempDir) -> EventStoreConfig {
+    let mut store = FileEventStore::open(dir.path()).unwrap();
+    store.append(&events(0..1)).unwrap();
+    let record_len = fs::metadata(&segments(dir)[0]).unwrap().len();
+    fs::remove_file(&segments(dir)[0]).unwrap();
+
+    EventStoreConfig {
+        max_segment_bytes: record_len * 5 / 2,
+    }
+}
+
+#[test]
+fn appends_roll_over_to_a_new_segment_past_the_size_limit() {
+    let dir = TempDir::new("event-store-rollover");
+    let config = two_per_segment(&dir);
+    let mut store = FileEventStore::open_with_config(dir.path(), config).unwrap();
+
+    assert_eq!(store.append(&events(0..5)).unwrap(), 0);
+
+    let names: Vec<_> = segments(&dir)
+        .iter()
+        .map(|path| path.file_name().unwrap().to_str().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "events-00000000000000000000.jsonl",
+            "events-00000000000000000002.jsonl",
+            "events-00000000000000000004.jsonl",
+        ]
+    );
+    for segment in segments(&dir) {
+        assert!(fs::metadata(segment).unwrap().len() <= config.max_segment_bytes);
+    }
+    assert_eq!(offsets(&store, 0), [0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn a_record_larger_than_a_segment_gets_one_to_itself() {
+    let dir = TempDir::new("event-store-oversized");
+    let config = EventStoreConfig {
+        max_segment_bytes: 1,
+    };
+    let mut store = FileEventStore::open_with_config(dir.path(), config).unwrap();
+
+    store.append(&events(0..3)).unwrap();
+
+    assert_eq!(segments(&dir).len(), 3);
+    assert_eq!(offsets(&store, 0), [0, 1, 2]);
+}
+
+#[test]
+fn reopening_cuts_off_a_torn_final_line() {
+    let dir = TempDir::new("event-store-torn");
+    let mut store = FileEventStore::open(dir.path()).unwrap();
+    store.append(&events(0..3)).unwrap();
+    drop(store);
+
+    let segment = segments(&dir).pop().unwrap();
+    let intact_len = fs::metadata(&segment).unwrap().len();
+    let mut file = OpenOptions::new().append(true).open(&segment).unwrap();
+    file.write_all(b"3|0badf00d|{\"schema_version\":").unwrap();
+    drop(file);
+
+    let mut store = FileEventStore::open(dir.path()).unwrap();
+    assert_eq!(store.next_offset(), 3);
+    assert_eq!(fs::metadata(&segment).unwrap().len(), intact_len);
+    assert_eq!(offsets(&store, 0), [0, 1, 2]);
+
+    store.append(&events(3..4)).unwrap();
+    let mut store = FileEventStore::open(dir.path()).unwrap();
+    assert_eq!(offsets(&store, 0), [0, 1, 2, 3]);
+    assert_eq!(store.append(&events(4..5)).unwrap(), 4);
+}
+
+#[test]
+fn reopening_continues_in_the_last_segment() {
+    let dir = TempDir::new("event-store-reopen");
+    let config = two_per_segment(&dir);
+    let mut store = FileEventStore::open_with_config(dir.path(), config).unwrap();
+    store.append(&events(0..3)).unwrap();
+    drop(store);
+
+    let mut store = FileEventStore::open_with_config(dir.path(), config).unwrap();
+    assert_eq!(store.next_offset(), 3);
+    store.append(&events(3..5)).unwrap();
+
+    assert_eq!(segments(&dir).len(), 3);
+    assert_eq!(offsets(&store, 0), [0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn read_from_starts_mid_log_and_crosses_segments() {
+    let dir = TempDir::new("event-store-read-from");
+    let config = two_per_segment(&dir);
+    let mut store = FileEventStore::open_with_config(dir.path(), config).unwrap();
+    store.append(&events(0..7)).unwrap();
+    assert_eq!(segments(&dir).len(), 4);
+
+    // 3 is the second record of the segment starting at 2
+    assert_eq!(offsets(&store, 3), [3, 4, 5, 6]);
+    assert_eq!(offsets(&store, 4), [4, 5, 6]);
+    assert_eq!(offsets(&store, 6), [6]);
+    assert!(offsets(&store, 7).is_empty());
+}
+
+#[test]
+fn appends_must_continue_the_log_without_gaps() {
+    let dir = TempDir::new("event-store-gap");
+    let mut store = FileEventStore::open(dir.path()).unwrap();
+    store.append(&events(0..2)).unwrap();
+
+    let gapped = [event(2), event(4)];
+    assert!(matches!(
+        store.append(&gapped),
+        Err(EventStoreError::OffsetMismatch {
+            expected: 3,
+            found: 4
+        })
+    ));
+    assert!(matches!(
+        store.append(&events(1..2)),
+        Err(EventStoreError::OffsetMismatch {
+            expected: 2,
+            found: 1
+        })
+    ));
+
+    // A rejected batch writes nothing, not even its valid prefix
+    assert_eq!(store.next_offset(), 2);
+    assert_eq!(offsets(&store, 0), [0, 1]);
+}
+
+#[test]
+fn opening_rejects_a_gap_between_stored_records() {
+    let dir = TempDir::new("event-store-stored-gap");
+    let mut store = FileEventStore::open(dir.path()).unwrap();
+    store.append(&events(0..3)).unwrap();
+    drop(store);
+
+    let segment = segments(&dir).pop().unwrap();
+    let contents = fs::read_to_string(&segment).unwrap();
+    let without_second: Vec<_> = contents
+        .lines()
+        .enumerate()
+        .filter(|(index, _)| *index != 1)
+        .map(|(_, line)| format!("{line}\n"))
+        .collect();
+    fs::write(&segment, without_second.concat()).unwrap();
+
+    match FileEventStore::open(dir.path()) {
+        Err(EventStoreError::Corrupted { line, reason, .. }) => {
+            assert_eq!(line, 2);
+            assert_eq!(reason, "expected offset 1, found 2");
+        }
+        other => panic!("expected a corrupted segment, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn checksums_catch_altered_records() {
+    let dir = TempDir::new("event-store-checksum");
+    let mut store = FileEventStore::open(dir.path()).unwrap();
+    store.append(&events(0..3)).unwrap();
+
+    let segment = segments(&dir).pop().unwrap();
+    let contents = fs::read_to_string(&segment).unwrap();
+    let (first, rest) = contents.split_once('\n').unwrap();
+    let (second, rest) = rest.split_once('\n').unwrap();
+    let altered = second.replacen("\"dt\":1.5", "\"dt\":2.5", 1);
+    assert_ne!(altered, second);
+    fs::write(&segment, format!("{first}\n{altered}\n{rest}")).unwrap();
+
+    let mut read = store.read_from(0).unwrap();
+    assert_eq!(read.next().unwrap().unwrap().offset, 0);
+    match read.next() {
+        Some(Err(EventStoreError::Corrupted { line, reason, .. })) => {
+            assert_eq!(line, 2);
+            assert_eq!(reason, "checksum mismatch");
+        }
+        other => panic!("expected a corrupted record, got {other:?}"),
+    }
+    // Reading stops at the first corrupted record
+    assert!(read.next().is_none());
+
+    assert!(matches!(
+        FileEventStore::open(dir.path()),
+        Err(EventStoreError::Corrupted { line: 2, .. })
+    ));
+}
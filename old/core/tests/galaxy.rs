@@ -0,0 +1,41 @@
+use outpost_3_core::systems::galaxy::MAX_STAR_COUNT;
+use outpost_3_core::systems::{self, ReducerContext, ReducerResult};
+use outpost_3_core::*;
+
+fn initialize(state: GameState, seed: u64, star_count: u32) -> ReducerResult {
+    let ctx = ReducerContext::new(&state, 0);
+    systems::reduce(state, Command::InitializeGalaxy { seed, star_count }, ctx)
+}
+
+#[test]
+fn initializing_a_galaxy_keeps_the_game_seed() {
+    let (state, events) = initialize(GameState::new().with_seed(7), 42, 20).unwrap();
+
+    assert_eq!(state.seed, 7);
+    assert_eq!(state.systems.len(), 20);
+    assert_eq!(state.sol().unwrap().name, "Sol");
+    assert!(matches!(
+        events[0].payload,
+        EventPayload::GalaxyInitialized { seed: 42, .. }
+    ));
+
+    // The same galaxy seed gives the same galaxy whatever the game seed
+    let (other, _) = initialize(GameState::new().with_seed(8), 42, 20).unwrap();
+    assert_eq!(
+        serde_json::to_value(&state.systems).unwrap(),
+        serde_json::to_value(&other.systems).unwrap()
+    );
+}
+
+#[test]
+fn star_count_must_be_within_bounds() {
+    for star_count in [0, MAX_STAR_COUNT + 1, u32::MAX] {
+        assert_eq!(
+            initialize(GameState::new(), 42, star_count).unwrap_err(),
+            CommandError::InvalidStarCount { star_count }
+        );
+    }
+
+    let (state, _) = initialize(GameState::new(), 42, 1).unwrap();
+    assert_eq!(state.systems.len(), 1);
+}
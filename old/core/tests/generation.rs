@@ -0,0 +1,206 @@
+use outpost_3_core::rng::GameRng;
+use outpost_3_core::systems::exploration::generate_system;
+use outpost_3_core::systems::galaxy::generate_galaxy;
+use outpost_3_core::*;
+
+// A system generated around every star of a seeded galaxy, as seen by a probe
+// whose sensors resolve everything
+fn systems() -> Vec<StarSystem> {
+    let galaxy = generate_galaxy(500, &mut GameRng::from_seed_and_offset(7, 0));
+    galaxy[1..]
+        .iter()
+        .enumerate()
+        .map(|(i, detected)| {
+            let mut rng = GameRng::from_seed_and_offset(i as u64, 1);
+            generate_system(detected, 1.0, |_| false, &mut rng)
+        })
+        .collect()
+}
+
+fn planets(system: &StarSystem) -> impl Iterator<Item = &CelestialBody> {
+    system
+        .bodies
+        .iter()
+        .filter(|body| body.body_type == CelestialBodyType::Planet)
+}
+
+#[test]
+fn rocky_planets_form_inside_the_frost_line_and_giants_beyond() {
+    let (mut rocky, mut giants) = (0, 0);
+    for system in systems()
+        .iter()
+        .filter(|system| system.stars().count() == 1)
+    {
+        let frost_line = system.frost_line_au();
+        for planet in planets(system) {
+            match planet.composition {
+                Some(Composition::Rocky) => {
+                    assert!(planet.orbit_au < frost_line, "{} is rocky", planet.name);
+                    rocky += 1;
+                }
+                Some(Composition::GasGiant | Composition::IceGiant) => {
+                    assert!(planet.orbit_au >= frost_line, "{} is a giant", planet.name);
+                    giants += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+    assert!(rocky > 50 && giants > 50, "{rocky} rocky, {giants} giants");
+}
+
+#[test]
+fn moons_orbit_planets_and_are_lighter_than_them() {
+    let (mut terrestrial, mut with_moons) = (0, 0);
+    for system in systems() {
+        let moons = system
+            .bodies
+            .iter()
+            .filter(|body| body.body_type == CelestialBodyType::Moon);
+        for moon in moons {
+            let path = system.path_to_root(moon.id);
+            let parent = path.get(1).expect("moon without a parent");
+            assert_eq!(parent.body_type, CelestialBodyType::Planet);
+            assert!(
+                moon.mass < parent.mass,
+                "{} outweighs its planet",
+                moon.name
+            );
+            assert_eq!(path.last().unwrap().id, system.root_star().unwrap().id);
+        }
+
+        for planet in planets(&system) {
+            let moons = system.children(planet.id).count();
+            match planet.composition {
+                Some(Composition::GasGiant) => assert!(moons >= 2),
+                Some(Composition::IceGiant) => assert!(moons >= 1),
+                _ => {
+                    terrestrial += 1;
+                    with_moons += usize::from(moons > 0);
+                }
+            }
+        }
+    }
+
+    // Rocky and icy worlds only now and then have moons
+    let share = with_moons as f64 / terrestrial as f64;
+    assert!(
+        (0.15..0.45).contains(&share),
+        "{share} of terrestrial worlds have moons"
+    );
+}
+
+#[test]
+fn belts_fill_gaps_between_planets_inside_the_oort_cloud() {
+    let mut belts = 0;
+    for system in systems() {
+        let oort_cloud = system.oort_cloud.expect("no Oort cloud");
+        for belt in &system.belts {
+            assert!(
+                belt.inner_radius_au < belt.outer_radius_au,
+                "{} is empty",
+                belt.name
+            );
+            assert!(belt.outer_radius_au < oort_cloud.radius_au);
+
+            // No planet circling the same star orbits within the belt
+            let neighbours =
+                planets(&system).filter(|planet| planet.parent_id == Some(belt.parent_id));
+            for planet in neighbours {
+                let orbit = belt.inner_radius_au..=belt.outer_radius_au;
+                assert!(
+                    !orbit.contains(&planet.orbit_au),
+                    "{} crosses {}",
+                    planet.name,
+                    belt.name
+                );
+            }
+            belts += 1;
+        }
+    }
+    assert!(belts > 50, "only {belts} belts");
+}
+
+// Critical orbits of Holman & Wiegert (1999) in units of a pair's separation,
+// where `mu` is the share of the pair's mass in the star that is not circled:
+// stable S-type orbits around one star lie inside the first, stable P-type
+// orbits around both outside the second
+fn s_type_limit(mu: f64) -> f64 {
+    0.464 - 0.380 * mu
+}
+
+fn p_type_limit(mu: f64) -> f64 {
+    1.60 + 4.12 * mu - 5.09 * mu * mu
+}
+
+#[test]
+fn planets_of_multiple_stars_keep_to_stable_orbits() {
+    let (mut binaries, mut triples) = (0, 0);
+    let (mut s_type, mut p_type) = (0, 0);
+    for system in systems() {
+        let stars: Vec<&CelestialBody> = system.stars().collect();
+        match stars.len() {
+            2 => binaries += 1,
+            3 => triples += 1,
+            _ => continue,
+        }
+
+        // Each companion orbits every star closer in
+        let primary = stars[0];
+        let mut inner_mass = primary.mass;
+        for companion in &stars[1..] {
+            let mu = companion.mass / (inner_mass + companion.mass);
+            let s_limit = s_type_limit(mu) * companion.orbit_au;
+            let p_limit = p_type_limit(mu) * companion.orbit_au;
+            let around_primary =
+                planets(&system).filter(|planet| planet.parent_id == Some(primary.id));
+            for planet in around_primary {
+                if planet.orbit_au < s_limit {
+                    s_type += 1;
+                } else {
+                    assert!(planet.orbit_au >= p_limit, "{} is unstable", planet.name);
+                    p_type += 1;
+                }
+            }
+
+            // Seen from the companion, the stars closer in are its partner
+            let own_limit = s_type_limit(1.0 - mu) * companion.orbit_au;
+            let around_companion =
+                planets(&system).filter(|planet| planet.parent_id == Some(companion.id));
+            for planet in around_companion {
+                assert!(planet.orbit_au < own_limit, "{} is unstable", planet.name);
+                s_type += 1;
+            }
+            inner_mass += companion.mass;
+        }
+    }
+    assert!(
+        binaries > 50 && triples > 10,
+        "{binaries} binaries, {triples} triples"
+    );
+    assert!(
+        s_type > 50 && p_type > 50,
+        "{s_type} S-type, {p_type} P-type"
+    );
+}
+
+#[test]
+fn paths_to_the_root_stop_at_parent_cycles() {
+    let mut system = systems()
+        .into_iter()
+        .find(|system| system.bodies.len() > 3)
+        .unwrap();
+    let (first, second) = (system.bodies[1].id, system.bodies[2].id);
+    system.bodies[1].parent_id = Some(second);
+    system.bodies[2].parent_id = Some(first);
+
+    let path = system.path_to_root(first);
+    assert_eq!(path.len(), system.bodies.len());
+    assert!(
+        path.iter()
+            .all(|body| body.id == first || body.id == second)
+    );
+
+    system.bodies[1].parent_id = Some(first);
+    assert_eq!(system.path_to_root(first).len(), system.bodies.len());
+}
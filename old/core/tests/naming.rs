@@ -0,0 +1,292 @@
+use std::collections::HashSet;
+
+use outpost_3_core::rng::GameRng;
+use outpost_3_core::systems::exploration::generate_system;
+use outpost_3_core::systems::galaxy::generate_galaxy;
+use outpost_3_core::systems::{self, ReducerContext};
+use outpost_3_core::*;
+
+// Every name in the game, apart from stars that share their system's name.
+// Scan data on its way to Sol counts in place of what is known so far.
+fn names(state: &GameState) -> Vec<String> {
+    let mut names = Vec::new();
+    for known in state.systems.values() {
+        let system = state
+            .data_in_transit
+            .get(&known.id)
+            .map_or(known, |data| &data.system);
+        names.push(known.name.clone());
+        let bodies = system
+            .bodies
+            .iter()
+            .filter(|body| body.parent_id.is_some() || body.name != system.name)
+            .map(|body| body.name.clone());
+        names.extend(bodies);
+        names.extend(system.belts.iter().map(|belt| belt.name.clone()));
+    }
+    names
+}
+
+fn assert_unique(state: &GameState) {
+    let mut seen = HashSet::new();
+    for name in names(state) {
+        assert!(seen.insert(name.clone()), "{name:?} is used twice");
+    }
+}
+
+fn apply(state: GameState, offset: u64, command: Command) -> GameState {
+    let ctx = ReducerContext::new(&state, offset);
+    systems::reduce(state, command, ctx).unwrap().0
+}
+
+// A galaxy with its first star past Sol scanned, as if a probe had reported
+// back, and that star's system
+fn scanned_galaxy() -> (GameState, StarSystem) {
+    let mut rng = GameRng::from_seed_and_offset(3, 0);
+    let galaxy = generate_galaxy(20, &mut rng);
+    let system = (1..galaxy.len())
+        .map(|i| generate_system(&galaxy[i], 1.0, |_| false, &mut rng))
+        .find(|system| system.bodies.len() > 3)
+        .unwrap();
+
+    let state = GameState::new()
+        .with_galaxy_initialized(&galaxy)
+        .with_discovery_data_received(system.clone());
+    (state, system)
+}
+
+#[test]
+fn generated_names_are_numbered_past_taken_ones() {
+    let galaxy = generate_galaxy(20, &mut GameRng::from_seed_and_offset(3, 0));
+    let detected = &galaxy[1];
+    let generate = |is_taken: &dyn Fn(&str) -> bool| {
+        let mut rng = GameRng::from_seed_and_offset(5, 0);
+        generate_system(detected, 1.0, is_taken, &mut rng)
+    };
+
+    let free = generate(&|_| false);
+    assert!(free.bodies.len() > 1);
+    let taken: HashSet<String> = free
+        .bodies
+        .iter()
+        .skip(1)
+        .map(|body| body.name.clone())
+        .chain(free.belts.iter().map(|belt| belt.name.clone()))
+        .collect();
+
+    let numbered = generate(&|name| taken.contains(name));
+    assert_eq!(numbered.bodies[0].name, free.bodies[0].name);
+    // The same bodies, all named apart from the taken names
+    let numbered_names = numbered.bodies.iter().skip(1).map(|body| &body.name);
+    let numbered_names = numbered_names.chain(numbered.belts.iter().map(|belt| &belt.name));
+    assert_eq!(numbered_names.clone().count(), taken.len());
+    for name in numbered_names {
+        assert!(!taken.contains(name), "{name:?} is taken");
+    }
+    for (free, body) in free.bodies.iter().zip(&numbered.bodies) {
+        assert_eq!(body.id, free.id);
+    }
+    // The first planet around the primary is numbered, not relettered
+    let first_planet = free
+        .bodies
+        .iter()
+        .find(|body| body.body_type == CelestialBodyType::Planet)
+        .unwrap();
+    assert_eq!(
+        numbered.body(first_planet.id).unwrap().name,
+        format!("{} (2)", first_planet.name)
+    );
+}
+
+#[test]
+fn a_scan_never_reuses_a_name_a_player_chose() {
+    let (state, scanned) = scanned_galaxy();
+    let target = state
+        .systems
+        .values()
+        .filter(|system| !system.is_discovered())
+        .min_by(|a, b| a.distance_from_sol.total_cmp(&b.distance_from_sol))
+        .unwrap()
+        .clone();
+
+    // Claim the names the target's first planets and belt would get
+    let mut state = state;
+    let mut offset = 0;
+    let claims = [
+        format!("{} b", target.name),
+        format!("{} c", target.name),
+        format!("{} Belt I", target.name),
+    ];
+    for (body, name) in scanned.bodies.iter().skip(1).zip(claims) {
+        state = apply(
+            state,
+            offset,
+            Command::RenameEntity {
+                system_id: scanned.id,
+                body_id: Some(body.id),
+                name,
+            },
+        );
+        offset += 1;
+    }
+
+    state = apply(
+        state,
+        offset,
+        Command::LaunchProbe {
+            target_system_id: target.id,
+            origin_system_id: None,
+            probe_class: ProbeClass::DeepSurvey,
+            waypoints: Vec::new(),
+        },
+    );
+    while !state.systems[&target.id].is_discovered() {
+        assert!(
+            !state.probes_in_flight.is_empty() || !state.data_in_transit.is_empty(),
+            "the probe was lost"
+        );
+        offset += 100;
+        state = apply(
+            state,
+            offset,
+            Command::AdvanceTime {
+                dt: 10.0 * HOURS_PER_YEAR,
+            },
+        );
+        assert_unique(&state);
+    }
+    let system = &state.systems[&target.id];
+    assert!(system.bodies.iter().any(|body| body.name.ends_with(" (2)")));
+}
+
+#[test]
+fn renaming_a_system_renames_its_star() {
+    let (state, scanned) = scanned_galaxy();
+    let old_name = scanned.name.clone();
+
+    let state = apply(
+        state,
+        0,
+        Command::RenameEntity {
+            system_id: scanned.id,
+            body_id: None,
+            name: "Halcyon".to_string(),
+        },
+    );
+    let system = &state.systems[&scanned.id];
+    assert_eq!(system.name, "Halcyon");
+    assert_eq!(system.root_star().unwrap().name, "Halcyon");
+    assert!(!state.is_name_taken(&old_name));
+
+    // A star named apart from its system keeps its own name
+    let star_id = system.root_star().unwrap().id;
+    let state = apply(
+        state,
+        1,
+        Command::RenameEntity {
+            system_id: scanned.id,
+            body_id: Some(star_id),
+            name: "Halcyon Prime".to_string(),
+        },
+    );
+    let state = apply(
+        state,
+        2,
+        Command::RenameEntity {
+            system_id: scanned.id,
+            body_id: None,
+            name: "Zephyr".to_string(),
+        },
+    );
+    let system = &state.systems[&scanned.id];
+    assert_eq!(system.name, "Zephyr");
+    assert_eq!(system.root_star().unwrap().name, "Halcyon Prime");
+}
+
+#[test]
+fn renames_only_clash_with_names_the_player_knows() {
+    // A scan too coarse to resolve moons or belts, and another system whose
+    // scan data is still on its way to Sol
+    let mut rng = GameRng::from_seed_and_offset(3, 0);
+    let galaxy = generate_galaxy(100, &mut rng);
+    let mut scans =
+        (1..galaxy.len()).map(|i| generate_system(&galaxy[i], 0.6, |_| false, &mut rng));
+    let scanned = scans
+        .find(|system| {
+            system
+                .bodies
+                .iter()
+                .any(|body| body.body_type == CelestialBodyType::Moon)
+                && system
+                    .bodies
+                    .iter()
+                    .any(|body| body.body_type == CelestialBodyType::Planet)
+        })
+        .unwrap();
+    let in_transit = scans.find(|system| system.bodies.len() > 1).unwrap();
+    let state = GameState::new()
+        .with_galaxy_initialized(&galaxy)
+        .with_discovery_data_received(scanned.clone())
+        .with_discovery_data_transmitted(in_transit.clone(), 1.0);
+
+    let planet = scanned
+        .bodies
+        .iter()
+        .find(|body| body.body_type == CelestialBodyType::Planet)
+        .unwrap();
+    let moon = scanned
+        .bodies
+        .iter()
+        .find(|body| body.discovery_level == DiscoveryLevel::Unknown)
+        .unwrap();
+    let rename = |state: &GameState, name: &str| {
+        let command = Command::RenameEntity {
+            system_id: scanned.id,
+            body_id: Some(planet.id),
+            name: name.to_string(),
+        };
+        systems::reduce(state.clone(), command, ReducerContext::new(state, 0))
+            .map(|(state, _)| state)
+    };
+
+    // Turning a name down would give away that something goes by it
+    assert!(rename(&state, &moon.name).is_ok());
+    assert!(rename(&state, &in_transit.bodies[1].name).is_ok());
+    assert!(state.is_name_taken(&moon.name));
+
+    let known = state
+        .systems
+        .values()
+        .find(|system| system.id != scanned.id)
+        .unwrap();
+    assert_eq!(
+        rename(&state, &known.name).unwrap_err(),
+        CommandError::NameTaken {
+            name: known.name.clone()
+        }
+    );
+    let state = rename(&state, "Halcyon").unwrap();
+    assert!(state.is_name_known("Halcyon"));
+    assert!(!state.is_name_taken(&planet.name));
+}
+
+#[test]
+fn the_name_index_is_rebuilt_on_load() {
+    let (state, scanned) = scanned_galaxy();
+    let state = apply(
+        state,
+        0,
+        Command::RenameEntity {
+            system_id: scanned.id,
+            body_id: Some(scanned.bodies[1].id),
+            name: "Halcyon".to_string(),
+        },
+    );
+
+    let loaded: GameState = serde_json::from_value(serde_json::to_value(&state).unwrap()).unwrap();
+    assert!(loaded.is_name_known("Halcyon"));
+    assert!(!loaded.is_name_taken(&scanned.bodies[1].name));
+    for name in names(&state) {
+        assert!(loaded.is_name_known(&name), "{name:?} was forgotten");
+    }
+}
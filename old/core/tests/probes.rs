@@ -0,0 +1,424 @@
+use outpost_3_core::rng::GameRng;
+use outpost_3_core::systems::exploration::{self, calculate_travel_time, probe_position};
+use outpost_3_core::systems::{self, ReducerContext};
+use outpost_3_core::*;
+
+/// Reduces commands one after another, the way an FFI session does
+struct Game {
+    state: GameState,
+    events: Vec<EventEnvelope>,
+}
+
+impl Game {
+    fn new() -> Self {
+        let mut game = Self {
+            state: GameState::new().with_seed(5),
+            events: Vec::new(),
+        };
+        game.apply(Command::InitializeGalaxy {
+            seed: 11,
+            star_count: 1000,
+        })
+        .unwrap();
+        game
+    }
+
+    // Reduce `command`, returning the events it emitted
+    fn apply(&mut self, command: Command) -> Result<Vec<EventEnvelope>, CommandError> {
+        let ctx = ReducerContext::new(&self.state, self.events.len() as u64);
+        let (state, events) = systems::reduce(self.state.clone(), command, ctx)?;
+        self.state = state;
+        self.events.extend(events.iter().cloned());
+        Ok(events)
+    }
+
+    fn launch(
+        &mut self,
+        target_system_id: StarSystemId,
+        probe_class: ProbeClass,
+        waypoints: Vec<StarSystemId>,
+    ) -> ProbeId {
+        let events = self
+            .apply(Command::LaunchProbe {
+                target_system_id,
+                origin_system_id: None,
+                probe_class,
+                waypoints,
+            })
+            .unwrap();
+        match events[0].payload {
+            EventPayload::ProbeLaunched { probe_id, .. } => probe_id,
+            _ => panic!("no launch"),
+        }
+    }
+
+    fn advance(&mut self, dt: f64) -> Vec<EventEnvelope> {
+        self.apply(Command::AdvanceTime { dt }).unwrap()
+    }
+
+    // Stars other than Sol, nearest first
+    fn stars_by_distance(&self) -> Vec<&StarSystem> {
+        let mut stars: Vec<_> = self
+            .state
+            .systems
+            .values()
+            .filter(|system| system.distance_from_sol > 0.0)
+            .collect();
+        stars.sort_by(|a, b| a.distance_from_sol.total_cmp(&b.distance_from_sol));
+        stars
+    }
+}
+
+// The course set by the single redirect or recall among `events`
+fn new_course(events: &[EventEnvelope]) -> &ProbeCourse {
+    match events {
+        [event] => match &event.payload {
+            EventPayload::ProbeRedirected { course, .. }
+            | EventPayload::ProbeRecalled { course, .. } => course,
+            other => panic!("expected a course change, got {other:?}"),
+        },
+        _ => panic!("expected one event, got {events:?}"),
+    }
+}
+
+// Advance a decade at a time until the probes are out of service, returning
+// the events about them, incidents aside
+fn fly(game: &mut Game, probe_ids: &[ProbeId]) -> Vec<EventPayload> {
+    let mut events = Vec::new();
+    while probe_ids
+        .iter()
+        .any(|probe_id| game.state.probes_in_flight.contains_key(probe_id))
+    {
+        events.extend(game.advance(10.0 * HOURS_PER_YEAR));
+    }
+    events
+        .into_iter()
+        .map(|event| event.payload)
+        .filter(|payload| match payload {
+            EventPayload::ProbeArrived { probe_id, .. }
+            | EventPayload::ProbeDeparted { probe_id, .. }
+            | EventPayload::ProbeRetired { probe_id, .. } => probe_ids.contains(probe_id),
+            EventPayload::ProbeLost { .. } => panic!("the probe was lost"),
+            EventPayload::DiscoveryDataTransmitted { .. } => true,
+            _ => false,
+        })
+        .collect()
+}
+
+// Stops `count` short hops apart, starting from the star nearest Sol
+fn nearby_stops(game: &Game, count: usize) -> Vec<StarSystemId> {
+    let mut stops = vec![game.stars_by_distance()[0]];
+    while stops.len() < count {
+        let last = stops.last().unwrap().position;
+        let next = game
+            .stars_by_distance()
+            .into_iter()
+            .filter(|system| stops.iter().all(|stop| stop.id != system.id))
+            .min_by(|a, b| {
+                let distance = |system: &StarSystem| system.position.distance_to(&last);
+                distance(a).total_cmp(&distance(b))
+            })
+            .unwrap();
+        stops.push(next);
+    }
+    stops.iter().map(|system| system.id).collect()
+}
+
+fn launched_eta(events: &[EventEnvelope], probe_id: ProbeId) -> f64 {
+    events
+        .iter()
+        .find_map(|event| match event.payload {
+            EventPayload::ProbeLaunched {
+                probe_id: launched,
+                eta,
+                ..
+            } if launched == probe_id => Some(eta),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn arrivals_happen_in_time_order_at_their_eta() {
+    let mut game = Game::new();
+    let targets: Vec<StarSystemId> = game.stars_by_distance()[..3]
+        .iter()
+        .map(|system| system.id)
+        .collect();
+
+    // Launch the furthest first, so arrivals come in the reverse order
+    let mut etas = Vec::new();
+    for &target in targets.iter().rev() {
+        let probe_id = game.launch(target, ProbeClass::DeepSurvey, Vec::new());
+        etas.push((probe_id, launched_eta(&game.events, probe_id)));
+    }
+    let last_eta = etas.iter().map(|(_, eta)| *eta).fold(0.0, f64::max);
+
+    let end_time = last_eta + HOURS_PER_YEAR;
+    let events = game.advance(end_time - game.state.game_time);
+    let arrivals: Vec<(ProbeId, f64)> = events
+        .iter()
+        .filter_map(|event| match event.payload {
+            EventPayload::ProbeArrived { probe_id, .. } => Some((probe_id, event.game_time)),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(arrivals.len(), 3);
+    assert!(arrivals.windows(2).all(|pair| pair[0].1 < pair[1].1));
+    for (probe_id, game_time) in &arrivals {
+        let (_, eta) = etas.iter().find(|(id, _)| id == probe_id).unwrap();
+        assert_eq!(game_time, eta);
+        assert_ne!(*game_time, end_time);
+    }
+    // Launched furthest first, arrived nearest first
+    let arrival_order: Vec<ProbeId> = arrivals.iter().map(|(probe_id, _)| *probe_id).collect();
+    let launch_order: Vec<ProbeId> = etas.iter().rev().map(|(probe_id, _)| *probe_id).collect();
+    assert_eq!(arrival_order, launch_order);
+}
+
+#[test]
+fn redirected_probes_set_off_from_where_they_are() {
+    let mut game = Game::new();
+    let stars: Vec<StarSystem> = game.stars_by_distance()[..2]
+        .iter()
+        .map(|system| (*system).clone())
+        .collect();
+    let (first, second) = (&stars[0], &stars[1]);
+    let probe_id = game.launch(first.id, ProbeClass::Survey, Vec::new());
+    game.advance(HOURS_PER_YEAR);
+
+    let probe = game.state.probes_in_flight[&probe_id].clone();
+    let now = game.state.game_time;
+    let events = game
+        .apply(Command::RedirectProbe {
+            probe_id,
+            target_system_id: second.id,
+        })
+        .unwrap();
+    let course = new_course(&events);
+
+    // Part of the way to the first star, on the line from Sol
+    let position = probe_position(&probe, &first.position, now);
+    assert_eq!(course.departure, position);
+    let covered = position.distance_to(&Position::ORIGIN);
+    assert!(covered > 0.0 && covered < first.distance_from_sol);
+    let to_first = position.distance_to(&first.position);
+    assert!((covered + to_first - first.distance_from_sol).abs() < 1e-9);
+
+    // The new leg is timed from there, not from Sol
+    let distance_ly = position.distance_to(&second.position);
+    let travel_time = calculate_travel_time(distance_ly, ProbeClass::Survey);
+    assert_eq!(course.target_system_id, second.id);
+    assert_eq!(course.distance_ly, distance_ly);
+    assert_eq!(course.eta, now + travel_time.galaxy_frame_hours);
+    assert_eq!(course.ship_frame_hours, travel_time.ship_frame_hours);
+    let from_sol = calculate_travel_time(second.distance_from_sol, ProbeClass::Survey);
+    assert_ne!(course.eta, probe.launched_at + from_sol.galaxy_frame_hours);
+
+    let probe = &game.state.probes_in_flight[&probe_id];
+    assert_eq!(probe.target_system_id, second.id);
+    assert_eq!(probe.departure, position);
+    assert_eq!(probe.departed_at, now);
+    assert_eq!(probe.arrival_time, course.eta);
+    assert_eq!(probe.distance_flown_ly, covered);
+}
+
+#[test]
+fn recalled_probes_head_back_to_their_origin() {
+    let mut game = Game::new();
+    let stars: Vec<StarSystem> = game.stars_by_distance()[..2]
+        .iter()
+        .map(|system| (*system).clone())
+        .collect();
+    let sol = game.state.sol().unwrap().id;
+
+    // From Sol, the probe returns to Sol
+    let probe_id = game.launch(stars[0].id, ProbeClass::Survey, Vec::new());
+    game.advance(HOURS_PER_YEAR);
+    let events = game.apply(Command::RecallProbe { probe_id }).unwrap();
+    assert_eq!(new_course(&events).target_system_id, sol);
+
+    // From a scanned system, it returns there
+    let mut rng = GameRng::from_seed_and_offset(1, 0);
+    let origin = exploration::generate_system(&stars[1], 1.0, |_| false, &mut rng);
+    game.state = game.state.clone().with_discovery_data_received(origin);
+    let events = game
+        .apply(Command::LaunchProbe {
+            target_system_id: stars[0].id,
+            origin_system_id: Some(stars[1].id),
+            probe_class: ProbeClass::Survey,
+            waypoints: vec![sol],
+        })
+        .unwrap();
+    let EventPayload::ProbeLaunched { probe_id, .. } = events[0].payload else {
+        panic!("no launch");
+    };
+    game.advance(HOURS_PER_YEAR);
+    let events = game.apply(Command::RecallProbe { probe_id }).unwrap();
+    assert_eq!(new_course(&events).target_system_id, stars[1].id);
+    // A recall drops the rest of the itinerary
+    assert!(game.state.probes_in_flight[&probe_id].waypoints.is_empty());
+}
+
+#[test]
+fn redirects_need_a_known_probe_and_a_new_target() {
+    let mut game = Game::new();
+    let target = game.stars_by_distance()[0].id;
+    let probe_id = game.launch(target, ProbeClass::Survey, Vec::new());
+
+    let unknown = ProbeId::new();
+    assert_eq!(
+        game.apply(Command::RedirectProbe {
+            probe_id: unknown,
+            target_system_id: target,
+        })
+        .unwrap_err(),
+        CommandError::UnknownProbe { probe_id: unknown }
+    );
+    assert_eq!(
+        game.apply(Command::RedirectProbe {
+            probe_id,
+            target_system_id: target,
+        })
+        .unwrap_err(),
+        CommandError::AlreadyEnRoute {
+            probe_id,
+            system_id: target,
+        }
+    );
+    assert_eq!(
+        game.apply(Command::RecallProbe { probe_id: unknown })
+            .unwrap_err(),
+        CommandError::UnknownProbe { probe_id: unknown }
+    );
+}
+
+#[test]
+fn itineraries_scan_each_stop_and_end_at_the_last() {
+    let mut game = Game::new();
+    let stops = nearby_stops(&game, 3);
+    let probe_id = game.launch(stops[0], ProbeClass::DeepSurvey, stops[1..].to_vec());
+
+    let events = fly(&mut game, &[probe_id]);
+    assert_eq!(events.len(), 8, "{events:?}");
+    for (hop, events) in events.chunks(3).enumerate() {
+        assert!(matches!(
+            events[0],
+            EventPayload::ProbeArrived { system_id, .. } if system_id == stops[hop]
+        ));
+        assert!(matches!(
+            &events[1],
+            EventPayload::DiscoveryDataTransmitted { system, .. } if system.id == stops[hop]
+        ));
+        if let Some(departed) = events.get(2) {
+            assert!(matches!(
+                departed,
+                EventPayload::ProbeDeparted { course, .. }
+                    if course.target_system_id == stops[hop + 1]
+            ));
+        }
+    }
+    // Removed after the last hop, every stop scanned
+    assert!(!game.state.probes_in_flight.contains_key(&probe_id));
+    assert!(
+        game.state
+            .schedule
+            .iter()
+            .all(|entry| entry.action != ScheduledAction::ProbeArrival { probe_id })
+    );
+    for stop in &stops {
+        let scanned = game.state.systems[stop].is_discovered();
+        assert!(scanned || game.state.data_in_transit.contains_key(stop));
+    }
+}
+
+#[test]
+fn probes_retire_when_the_next_stop_is_out_of_range() {
+    let mut game = Game::new();
+    let first = game.stars_by_distance()[0].clone();
+    let range_ly = ProbeClass::Scout.spec().max_range_ly;
+    let out_of_reach = game
+        .stars_by_distance()
+        .into_iter()
+        .find(|system| {
+            let distance_ly = first.position.distance_to(&system.position);
+            distance_ly > 0.0
+                && distance_ly < range_ly
+                && first.distance_from_sol + distance_ly > range_ly
+        })
+        .unwrap()
+        .id;
+    let probe_id = game.launch(first.id, ProbeClass::Scout, vec![out_of_reach]);
+
+    let events = fly(&mut game, &[probe_id]);
+    assert_eq!(events.len(), 3, "{events:?}");
+    assert!(matches!(events[0], EventPayload::ProbeArrived { .. }));
+    assert!(matches!(
+        events[1],
+        EventPayload::DiscoveryDataTransmitted { .. }
+    ));
+    assert!(matches!(
+        events[2],
+        EventPayload::ProbeRetired { system_id, .. } if system_id == first.id
+    ));
+    assert!(!game.state.data_in_transit.contains_key(&out_of_reach));
+}
+
+#[test]
+fn probes_may_share_later_waypoints() {
+    let mut game = Game::new();
+    let stars = nearby_stops(&game, 3);
+    let first = game.launch(stars[0], ProbeClass::DeepSurvey, vec![stars[2]]);
+    let second = game.launch(stars[1], ProbeClass::DeepSurvey, vec![stars[2]]);
+
+    // Both call at the shared stop, which is only scanned once
+    let events = fly(&mut game, &[first, second]);
+    let arrivals = events
+        .iter()
+        .filter(|payload| {
+            matches!(payload, EventPayload::ProbeArrived { system_id, .. } if *system_id == stars[2])
+        })
+        .count();
+    let scans = events
+        .iter()
+        .filter(|payload| {
+            matches!(payload, EventPayload::DiscoveryDataTransmitted { system, .. } if system.id == stars[2])
+        })
+        .count();
+    assert_eq!((arrivals, scans), (2, 1));
+}
+
+#[test]
+fn scan_data_reaches_sol_after_the_light_delay() {
+    let mut game = Game::new();
+    let target = game.stars_by_distance()[0].clone();
+    let probe_id = game.launch(target.id, ProbeClass::Survey, Vec::new());
+    let arrival = launched_eta(&game.events, probe_id);
+
+    while game.state.game_time < arrival {
+        game.advance((arrival - game.state.game_time).min(10.0 * HOURS_PER_YEAR));
+    }
+    let received_at = arrival + target.distance_from_sol * HOURS_PER_YEAR;
+    assert_eq!(
+        game.state.data_in_transit[&target.id].received_at,
+        received_at
+    );
+
+    // Known at home only once the signal has crossed the distance
+    game.advance(received_at - 1.0 - game.state.game_time);
+    assert_eq!(
+        game.state.systems[&target.id].discovery_level,
+        DiscoveryLevel::Detected
+    );
+    assert!(game.state.data_in_transit.contains_key(&target.id));
+
+    let events = game.advance(2.0);
+    let received = events
+        .iter()
+        .find(|event| matches!(event.payload, EventPayload::DiscoveryDataReceived { .. }))
+        .unwrap();
+    assert_eq!(received.game_time, received_at);
+    assert!(game.state.systems[&target.id].is_discovered());
+    assert!(game.state.data_in_transit.is_empty());
+}
@@ -0,0 +1,77 @@
+use outpost_3_core::rng::GameRng;
+use outpost_3_core::systems::reliability::roll_incidents;
+use outpost_3_core::*;
+
+const CENTURY_HOURS: f64 = 100.0 * HOURS_PER_YEAR;
+
+// A survey probe on a leg long enough for several incidents to be likely
+fn probe() -> ProbeInFlight {
+    let flight_hours = 5_000.0 * HOURS_PER_YEAR;
+    ProbeInFlight {
+        id: ProbeId::new(),
+        probe_class: ProbeClass::Survey,
+        target_system_id: StarSystemId::new(),
+        origin_system_id: None,
+        launched_at: 0.0,
+        departure: Position::ORIGIN,
+        departed_at: 0.0,
+        distance_ly: 10.0,
+        arrival_time: flight_hours,
+        ship_frame_hours: flight_hours,
+        condition: 0.8,
+        waypoints: Vec::new(),
+        distance_flown_ly: 0.0,
+    }
+}
+
+#[test]
+fn incidents_continue_until_the_probe_is_lost() {
+    let probe = probe();
+    let mut most_incidents = 0;
+
+    for seed in 0..200 {
+        let mut rng = GameRng::from_seed_and_offset(seed, 0);
+        let incidents = roll_incidents([&probe], CENTURY_HOURS, probe.arrival_time, &mut rng);
+        most_incidents = most_incidents.max(incidents.len());
+
+        let mut previous_time = CENTURY_HOURS;
+        let mut previous_condition = probe.condition;
+        for (index, (time, payload)) in incidents.iter().enumerate() {
+            assert!(*time >= previous_time && *time < probe.arrival_time);
+            previous_time = *time;
+
+            match payload {
+                EventPayload::ProbeDamaged { condition, .. } => {
+                    // Damage compounds on the condition left by the last incident
+                    assert!(*condition < previous_condition * 0.9);
+                    assert!(*condition >= previous_condition * 0.5);
+                    previous_condition = *condition;
+                }
+                EventPayload::ProbeLost { .. } => {
+                    assert_eq!(index, incidents.len() - 1, "incident after a loss");
+                }
+                other => panic!("unexpected incident {other:?}"),
+            }
+        }
+    }
+
+    assert!(
+        most_incidents > 1,
+        "never more than one incident per interval"
+    );
+}
+
+#[test]
+fn incidents_stay_within_the_interval() {
+    let probe = probe();
+    for seed in 0..200 {
+        let mut rng = GameRng::from_seed_and_offset(seed, 0);
+        let until = 2.0 * CENTURY_HOURS;
+        for (time, _) in roll_incidents([&probe], CENTURY_HOURS, until, &mut rng) {
+            assert!((CENTURY_HOURS..until).contains(&time));
+        }
+    }
+
+    let mut rng = GameRng::from_seed_and_offset(0, 0);
+    assert!(roll_incidents([&probe], CENTURY_HOURS, CENTURY_HOURS, &mut rng).is_empty());
+}
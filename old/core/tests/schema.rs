@@ -0,0 +1,262 @@
+use outpost_3_core::persistence::Snapshot;
+use outpost_3_core::persistence::schema::{self, CURRENT_SCHEMA_VERSION, DocumentKind};
+use outpost_3_core::*;
+use serde_json::Value;
+
+// Documents saved by earlier versions of the game, named after their schema
+// version. Each version left an event log and two snapshots: one with a
+// probe in flight, and one after it reported back.
+const FIXTURES: [(&str, &str, &str, &str); 6] = [
+    (
+        "v0",
+        include_str!("fixtures/schema/v0_events.jsonl"),
+        include_str!("fixtures/schema/v0_snapshot_in_flight.json"),
+        include_str!("fixtures/schema/v0_snapshot.json"),
+    ),
+    (
+        "v4",
+        include_str!("fixtures/schema/v4_events.jsonl"),
+        include_str!("fixtures/schema/v4_snapshot_in_flight.json"),
+        include_str!("fixtures/schema/v4_snapshot.json"),
+    ),
+    (
+        "v5",
+        include_str!("fixtures/schema/v5_events.jsonl"),
+        include_str!("fixtures/schema/v5_snapshot_in_flight.json"),
+        include_str!("fixtures/schema/v5_snapshot.json"),
+    ),
+    (
+        "v10",
+        include_str!("fixtures/schema/v10_events.jsonl"),
+        include_str!("fixtures/schema/v10_snapshot_in_flight.json"),
+        include_str!("fixtures/schema/v10_snapshot.json"),
+    ),
+    (
+        "v11",
+        include_str!("fixtures/schema/v11_events.jsonl"),
+        include_str!("fixtures/schema/v11_snapshot_in_flight.json"),
+        include_str!("fixtures/schema/v11_snapshot.json"),
+    ),
+    (
+        "v14",
+        include_str!("fixtures/schema/v14_events.jsonl"),
+        include_str!("fixtures/schema/v14_snapshot_in_flight.json"),
+        include_str!("fixtures/schema/v14_snapshot.json"),
+    ),
+];
+
+fn fixture(version: &str) -> (Vec<EventEnvelope>, Snapshot, Snapshot) {
+    let (_, events, in_flight, settled) = FIXTURES
+        .iter()
+        .find(|(name, ..)| *name == version)
+        .expect("fixture");
+    let events = events
+        .lines()
+        .map(|line| schema::decode(DocumentKind::Event, line).unwrap())
+        .collect();
+    (
+        events,
+        schema::decode(DocumentKind::Snapshot, in_flight).unwrap(),
+        schema::decode(DocumentKind::Snapshot, settled).unwrap(),
+    )
+}
+
+fn discovered_systems(events: &[EventEnvelope]) -> Vec<&StarSystem> {
+    events
+        .iter()
+        .filter_map(|event| match &event.payload {
+            EventPayload::DiscoveryDataReceived { system } => Some(system),
+            _ => None,
+        })
+        .collect()
+}
+
+// Decoding, encoding and decoding again must give the same document: the
+// upgraded data deserializes into the current types without losing anything.
+fn assert_round_trips<T>(kind: DocumentKind, json: &str)
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    let decoded: T = schema::decode(kind, json).unwrap();
+    let encoded = schema::encode(&decoded).unwrap();
+    let document: Value = serde_json::from_str(&encoded).unwrap();
+    assert_eq!(document["schema_version"], CURRENT_SCHEMA_VERSION);
+
+    let again: T = schema::decode(kind, &encoded).unwrap();
+    assert_eq!(
+        serde_json::to_value(&again).unwrap(),
+        serde_json::to_value(&decoded).unwrap()
+    );
+}
+
+#[test]
+fn every_fixture_upgrades_to_the_current_version() {
+    for (version, events, in_flight, settled) in FIXTURES {
+        for line in events.lines() {
+            assert_round_trips::<EventEnvelope>(DocumentKind::Event, line);
+        }
+        for snapshot in [in_flight, settled] {
+            assert_round_trips::<Snapshot>(DocumentKind::Snapshot, snapshot);
+        }
+
+        let (events, in_flight, settled) = fixture(version);
+        assert_eq!(in_flight.state.probes_in_flight.len(), 1, "{version}");
+        assert!(settled.state.probes_in_flight.is_empty(), "{version}");
+        assert_eq!(
+            discovered_systems(&events).len(),
+            1,
+            "{version} has no discovery event"
+        );
+    }
+}
+
+#[test]
+fn unversioned_documents_are_version_0() {
+    let (events, in_flight, _) = fixture("v0");
+
+    // Written after the seed was added, so `v0_to_v1` must keep it
+    assert_eq!(in_flight.state.seed, 1);
+    let probe = in_flight.state.probes_in_flight.values().next().unwrap();
+    assert_eq!(probe.probe_class, ProbeClass::Survey);
+    assert_eq!(probe.origin_system_id, None);
+    assert_eq!(probe.distance_ly, 4.37);
+    assert_eq!(probe.condition, 1.0);
+    assert_eq!(
+        probe.ship_frame_hours,
+        probe.arrival_time - probe.launched_at
+    );
+    assert!(probe.waypoints.is_empty());
+
+    // The probe in flight became a scheduled arrival
+    let entries: Vec<_> = in_flight.state.schedule.iter().collect();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].due, probe.arrival_time);
+    assert_eq!(
+        entries[0].action,
+        ScheduledAction::ProbeArrival { probe_id: probe.id }
+    );
+
+    let launched = events
+        .iter()
+        .find_map(|event| match &event.payload {
+            EventPayload::ProbeLaunched {
+                probe_id,
+                eta,
+                ship_frame_hours,
+                ..
+            } => Some((*probe_id, *eta, *ship_frame_hours)),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(
+        launched,
+        (probe.id, probe.arrival_time, probe.ship_frame_hours)
+    );
+}
+
+#[test]
+fn system_discovered_became_discovery_data_received() {
+    for version in ["v0", "v4"] {
+        let (events, ..) = fixture(version);
+        let system = discovered_systems(&events)[0];
+
+        // Bodies reported by a probe were scanned, and all circle the star
+        assert_eq!(system.discovery_level, DiscoveryLevel::Scanned);
+        let star = system.root_star().expect("star");
+        for body in &system.bodies {
+            assert_eq!(body.discovery_level, DiscoveryLevel::Scanned);
+            if body.id != star.id {
+                assert_eq!(body.parent_id, Some(star.id));
+            }
+        }
+    }
+}
+
+#[test]
+fn system_scanned_became_discovery_data_received() {
+    for version in ["v5", "v10"] {
+        let (events, ..) = fixture(version);
+        let system = discovered_systems(&events)[0];
+        assert_eq!(system.discovery_level, DiscoveryLevel::Scanned);
+        assert!(system.oort_cloud.is_some());
+    }
+}
+
+#[test]
+fn delayed_discovery_data_decodes_unchanged() {
+    let (events, ..) = fixture("v11");
+
+    let transmitted = events
+        .iter()
+        .find_map(|event| match &event.payload {
+            EventPayload::DiscoveryDataTransmitted {
+                system,
+                received_at,
+            } => Some((system, *received_at)),
+            _ => None,
+        })
+        .unwrap();
+    let received = events
+        .iter()
+        .find(|event| matches!(event.payload, EventPayload::DiscoveryDataReceived { .. }))
+        .unwrap();
+
+    assert_eq!(received.game_time, transmitted.1);
+    assert_eq!(discovered_systems(&events)[0].id, transmitted.0.id);
+}
+
+#[test]
+fn sol_is_recorded_by_id() {
+    let (_, in_flight, settled) = fixture("v14");
+    for snapshot in [in_flight, settled] {
+        let sol = snapshot.state.sol().expect("sol");
+        assert_eq!(sol.name, "Sol");
+        assert_eq!(sol.position, Position::ORIGIN);
+    }
+}
+
+#[test]
+fn asteroid_belt_bodies_became_belts() {
+    let (_, events, ..) = FIXTURES.iter().find(|(name, ..)| *name == "v14").unwrap();
+    let raw: Value = serde_json::from_str(
+        events
+            .lines()
+            .find(|line| line.contains("DiscoveryDataReceived"))
+            .unwrap(),
+    )
+    .unwrap();
+    let raw_belts: Vec<&Value> =
+        raw["data"]["payload"]["DiscoveryDataReceived"]["system"]["bodies"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|body| body["body_type"] == "AsteroidBelt")
+            .collect();
+    assert!(!raw_belts.is_empty());
+
+    let (events, ..) = fixture("v14");
+    let system = discovered_systems(&events)[0];
+    let star = system.root_star().expect("star");
+    assert_eq!(system.belts.len(), raw_belts.len());
+    for raw_belt in raw_belts {
+        let belt = system
+            .belts
+            .iter()
+            .find(|belt| serde_json::to_value(belt.id).unwrap() == raw_belt["id"])
+            .expect("belt kept its id");
+        assert!(
+            system.body(belt_body_id(raw_belt)).is_none(),
+            "belt is still a body"
+        );
+
+        let orbit_au = raw_belt["orbit_au"].as_f64().unwrap();
+        assert_eq!(belt.name, raw_belt["name"]);
+        assert_eq!(belt.parent_id, star.id);
+        assert_eq!(belt.inner_radius_au, orbit_au * 0.8);
+        assert_eq!(belt.outer_radius_au, orbit_au * 1.2);
+    }
+}
+
+fn belt_body_id(raw_belt: &Value) -> CelestialBodyId {
+    serde_json::from_value(raw_belt["id"].clone()).unwrap()
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use std::fs;
+
+use common::{TempDir, events};
+use outpost_3_core::persistence::*;
+use outpost_3_core::systems::replay;
+use outpost_3_core::*;
+
+fn snapshot_offsets(dir: &TempDir) -> Vec<u64> {
+    let mut offsets: Vec<u64> = fs::read_dir(dir.path())
+        .unwrap()
+        .filter_map(|entry| {
+            let name = entry.unwrap().file_name().into_string().unwrap();
+            name.strip_prefix("snapshot-")?
+                .strip_suffix(".json")?
+                .parse()
+                .ok()
+        })
+        .collect();
+    offsets.sort();
+    offsets
+}
+
+fn state_at(game_time: f64) -> GameState {
+    GameState::new().with_game_time(game_time)
+}
+
+#[test]
+fn saving_prunes_all_but_the_newest_snapshots() {
+    let dir = TempDir::new("snapshot-store-prune");
+    let config = SnapshotConfig {
+        every_n_events: 10,
+        retain: 2,
+    };
+    let mut store = SnapshotStore::open_with_config(dir.path(), config).unwrap();
+
+    for offset in [10, 20, 30, 40] {
+        store.save(offset, &state_at(offset as f64)).unwrap();
+    }
+
+    assert_eq!(snapshot_offsets(&dir), [30, 40]);
+}
+
+#[test]
+fn pruning_always_keeps_the_newest_snapshot() {
+    let dir = TempDir::new("snapshot-store-retain-none");
+    let config = SnapshotConfig {
+        every_n_events: 10,
+        retain: 0,
+    };
+    let mut store = SnapshotStore::open_with_config(dir.path(), config).unwrap();
+
+    store.save(10, &state_at(10.0)).unwrap();
+    store.save(20, &state_at(20.0)).unwrap();
+
+    assert_eq!(snapshot_offsets(&dir), [20]);
+}
+
+#[test]
+fn snapshots_are_taken_every_n_events() {
+    let dir = TempDir::new("snapshot-store-every-n");
+    let config = SnapshotConfig {
+        every_n_events: 10,
+        retain: 3,
+    };
+    let mut store = SnapshotStore::open_with_config(dir.path(), config).unwrap();
+
+    assert!(!store.maybe_save(9, &state_at(9.0)).unwrap());
+    assert!(store.maybe_save(10, &state_at(10.0)).unwrap());
+    assert!(!store.maybe_save(19, &state_at(19.0)).unwrap());
+
+    // A reopened store counts from its newest snapshot on disk
+    let mut store = SnapshotStore::open_with_config(dir.path(), config).unwrap();
+    assert!(!store.maybe_save(15, &state_at(15.0)).unwrap());
+    assert!(store.maybe_save(21, &state_at(21.0)).unwrap());
+    assert_eq!(snapshot_offsets(&dir), [10, 21]);
+}
+
+#[test]
+fn latest_valid_skips_a_corrupt_newest_snapshot() {
+    let dir = TempDir::new("snapshot-store-corrupt");
+    let mut store = SnapshotStore::open(dir.path()).unwrap();
+    store.save(10, &state_at(10.0)).unwrap();
+    store.save(20, &state_at(20.0)).unwrap();
+
+    let newest = dir.path().join("snapshot-00000000000000000020.json");
+    let record = fs::read_to_string(&newest).unwrap();
+    fs::write(&newest, &record[..record.len() / 2]).unwrap();
+
+    let snapshot = store.latest_valid(u64::MAX).unwrap().unwrap();
+    assert_eq!(snapshot.next_offset, 10);
+    assert_eq!(snapshot.state.game_time, 10.0);
+}
+
+#[test]
+fn latest_valid_ignores_snapshots_ahead_of_the_log() {
+    let dir = TempDir::new("snapshot-store-ahead");
+    let mut store = SnapshotStore::open(dir.path()).unwrap();
+    store.save(10, &state_at(10.0)).unwrap();
+    store.save(20, &state_at(20.0)).unwrap();
+
+    assert_eq!(store.latest_valid(19).unwrap().unwrap().next_offset, 10);
+    assert!(store.latest_valid(9).unwrap().is_none());
+}
+
+#[test]
+fn restore_replays_the_log_tail_onto_the_newest_snapshot() {
+    let dir = TempDir::new("snapshot-store-restore");
+    let mut events_store = FileEventStore::open(dir.path().join("events")).unwrap();
+    let log = events(0..5);
+    events_store.append(&log).unwrap();
+
+    // Seed the snapshot differently from `initial`, so only a state built on
+    // top of the snapshot carries it
+    let mut snapshots = SnapshotStore::open(dir.path().join("snapshots")).unwrap();
+    let prefix = replay(
+        GameState::new().with_seed(7),
+        log[..3].iter().map(|event| &event.payload),
+    );
+    snapshots.save(3, &prefix).unwrap();
+
+    let restored = snapshots.restore(&events_store, GameState::new()).unwrap();
+    assert_eq!(restored.seed, 7);
+    assert_eq!(restored.game_time, log[4].game_time);
+}
+
+#[test]
+fn restore_replays_the_whole_log_without_a_valid_snapshot() {
+    let dir = TempDir::new("snapshot-store-restore-full");
+    let mut events_store = FileEventStore::open(dir.path().join("events")).unwrap();
+    let log = events(0..5);
+    events_store.append(&log).unwrap();
+
+    let mut snapshots = SnapshotStore::open(dir.path().join("snapshots")).unwrap();
+    snapshots.save(3, &state_at(0.0).with_seed(7)).unwrap();
+    let path = dir
+        .path()
+        .join("snapshots")
+        .join("snapshot-00000000000000000003.json");
+    fs::write(&path, "3|00000000|{}").unwrap();
+
+    let restored = snapshots
+        .restore(&events_store, GameState::new().with_seed(1))
+        .unwrap();
+    assert_eq!(restored.seed, 1);
+    assert_eq!(restored.game_time, log[4].game_time);
+}
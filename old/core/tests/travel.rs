@@ -0,0 +1,131 @@
+use outpost_3_core::travel::*;
+
+const ONE_G: DriveProfile = DriveProfile {
+    acceleration_g: 1.0,
+    max_speed_c: 0.2,
+    deceleration_g: 1.0,
+};
+
+fn years(hours: f64) -> f64 {
+    hours / HOURS_PER_YEAR
+}
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() <= tolerance * expected.abs().max(1.0),
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn zero_distance_takes_no_time() {
+    assert_eq!(travel_time(0.0, &ONE_G), TravelTime::ZERO);
+}
+
+#[test]
+fn instant_acceleration_cruises_the_whole_way() {
+    let drive = DriveProfile {
+        acceleration_g: f64::INFINITY,
+        max_speed_c: 0.6,
+        deceleration_g: f64::INFINITY,
+    };
+    let trip = travel_time(12.0, &drive);
+
+    // Lorentz factor at 0.6c is exactly 1.25
+    assert_close(years(trip.galaxy_frame_hours), 12.0 / 0.6, 1e-12);
+    assert_close(years(trip.ship_frame_hours), 12.0 / 0.6 / 1.25, 1e-12);
+}
+
+#[test]
+fn uncapped_drive_matches_the_relativistic_rocket() {
+    let drive = DriveProfile {
+        max_speed_c: 1.0,
+        ..ONE_G
+    };
+    let distance = 4.37;
+    let trip = travel_time(distance, &drive);
+
+    // Accelerate to the midpoint, then decelerate: with `a` in ly/yr², each
+    // half takes acosh(aD/2 + 1) / a ship years and sinh of that galaxy years
+    let a = STANDARD_GRAVITY_LY_PER_YEAR2;
+    let half_rapidity = (a * distance / 2.0 + 1.0).acosh();
+    assert_close(years(trip.ship_frame_hours), 2.0 * half_rapidity / a, 1e-12);
+    assert_close(
+        years(trip.galaxy_frame_hours),
+        2.0 * half_rapidity.sinh() / a,
+        1e-12,
+    );
+}
+
+#[test]
+fn slow_drive_approaches_newtonian_motion() {
+    let drive = DriveProfile {
+        acceleration_g: 0.01,
+        max_speed_c: 1e-4,
+        deceleration_g: 0.01,
+    };
+    let distance = 0.5;
+    let trip = travel_time(distance, &drive);
+
+    // Classically, speeding up and slowing down costs v/a over a pure cruise
+    let v = drive.max_speed_c;
+    let a = drive.acceleration_g * STANDARD_GRAVITY_LY_PER_YEAR2;
+    assert_close(years(trip.galaxy_frame_hours), distance / v + v / a, 1e-6);
+    assert_close(trip.ship_frame_hours, trip.galaxy_frame_hours, 1e-8);
+}
+
+#[test]
+fn short_hops_never_reach_cruise_speed() {
+    let distance = 0.001;
+    let capped = travel_time(distance, &ONE_G);
+    let uncapped = travel_time(
+        distance,
+        &DriveProfile {
+            max_speed_c: 1.0,
+            ..ONE_G
+        },
+    );
+
+    assert_close(
+        capped.galaxy_frame_hours,
+        uncapped.galaxy_frame_hours,
+        1e-12,
+    );
+    assert_close(capped.ship_frame_hours, uncapped.ship_frame_hours, 1e-12);
+}
+
+#[test]
+fn nothing_outruns_light_and_clocks_on_board_run_slow() {
+    for distance in [0.1, 4.37, 50.0, 1000.0] {
+        for max_speed_c in [0.1, 0.5, 0.99, 1.0] {
+            let trip = travel_time(
+                distance,
+                &DriveProfile {
+                    max_speed_c,
+                    ..ONE_G
+                },
+            );
+            assert!(years(trip.galaxy_frame_hours) > distance);
+            assert!(trip.ship_frame_hours < trip.galaxy_frame_hours);
+        }
+    }
+}
+
+#[test]
+fn distance_covered_runs_from_departure_to_arrival() {
+    let distance = 4.37;
+    let trip = travel_time(distance, &ONE_G);
+    let at = |hours| distance_covered(distance, &ONE_G, hours);
+
+    assert_eq!(at(0.0), 0.0);
+    assert_close(at(trip.galaxy_frame_hours), distance, 1e-12);
+    // The same burn at both ends makes the trip symmetric about its midpoint
+    assert_close(at(trip.galaxy_frame_hours / 2.0), distance / 2.0, 1e-9);
+
+    let mut previous = 0.0;
+    for step in 1..=100 {
+        let covered = at(trip.galaxy_frame_hours * step as f64 / 100.0);
+        assert!(covered > previous);
+        previous = covered;
+    }
+}